@@ -5,6 +5,7 @@ pub use fixedbitset;
 pub use fxhash;
 pub use hashbrown;
 pub use parking_lot;
+pub use rayon;
 
 pub mod sparse_set;
 
@@ -28,6 +29,7 @@ pub mod prelude {
     pub use fxhash;
     pub use hashbrown;
     pub use parking_lot;
+    pub use rayon;
     pub use thiserror::Error;
 
     pub use super::{
@@ -15,6 +15,7 @@ use std::{
     },
     marker::PhantomData,
     ptr::NonNull,
+    slice,
 };
 
 /// An unsafe statically-unknown homogenous list data container, similar to [`Vec`]. Due to the
@@ -195,6 +196,42 @@ impl<'a> VecErased<'a> {
         self.len = len;
     }
 
+    /// Returns a raw pointer to the vector's buffer, which is dangling if nothing is allocated.
+    #[inline]
+    pub const fn as_ptr(&self) -> *const u8 {
+        self.array.as_ptr()
+    }
+
+    /// Returns the items as a typed slice.
+    ///
+    /// # Safety
+    /// `T` must be the data type of the vector.
+    #[inline]
+    pub unsafe fn as_slice<T: 'a>(&self) -> &[T] {
+        debug_assert_eq!(Layout::new::<T>(), self.layout);
+        slice::from_raw_parts(self.slice_ptr::<T>(), self.len)
+    }
+
+    /// Returns the items as a typed mutable slice.
+    ///
+    /// # Safety
+    /// `T` must be the data type of the vector.
+    #[inline]
+    pub unsafe fn as_mut_slice<T: 'a>(&mut self) -> &mut [T] {
+        debug_assert_eq!(Layout::new::<T>(), self.layout);
+        slice::from_raw_parts_mut(self.slice_ptr::<T>(), self.len)
+    }
+
+    /// Returns the buffer as a pointer to `T`, which is well-aligned even if nothing is allocated.
+    #[inline]
+    fn slice_ptr<T>(&self) -> *mut T {
+        if self.cap == 0 || self.layout.size() == 0 {
+            NonNull::<T>::dangling().as_ptr()
+        } else {
+            self.array.as_ptr().cast()
+        }
+    }
+
     /// Returns an untyped immutable pointer to the item at `index`, with bounds-checking.
     #[inline]
     pub fn get(&self, index: usize) -> Option<Ptr> {
@@ -627,6 +664,11 @@ mod tests {
             assert!(vec.cap >= 2);
             assert_eq!(*GLOBAL.read().unwrap(), 2);
 
+            // Slice check.
+            assert_eq!(vec.as_slice::<Data>(), [Data::new(159), Data::new(420)]);
+            vec.as_mut_slice::<Data>()[1].0 = 42;
+            assert_eq!(vec.get(1).unwrap().deref::<Data>(), &Data::new(42));
+
             // Shrink check.
             vec.shrink_to_fit();
             assert_eq!(vec.cap, vec.len);
@@ -13,16 +13,17 @@ use syn::{
     self,
     spanned::Spanned,
     DeriveInput,
-    Data, Error, Fields, Ident, Index, LitStr,
+    Data, Error, Fields, Ident, Index, LitStr, Path,
 };
 
-#[proc_macro_derive(Component, attributes(component))]
+#[proc_macro_derive(Component, attributes(component, relationship, relationship_target))]
 pub fn derive_component(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     match (move || -> syn::Result<TokenStream> {
         let mut input = syn::parse::<DeriveInput>(input)?;
         let fei_ecs = fei_macros::module("fei-ecs")?.ok_or_else(|| Error::new_spanned(&input, "`fei-ecs` is unavailable"))?;
 
         let mut storage = "Table".to_string();
+        let mut hooks = Vec::new();
         for meta in input.attrs.iter().filter(|&attr| attr.path().is_ident("component")) {
             meta.parse_nested_meta(|meta| if meta.path.is_ident("storage") {
                 storage = match meta.value()?.parse::<LitStr>()?.value() {
@@ -30,11 +31,47 @@ pub fn derive_component(input: proc_macro::TokenStream) -> proc_macro::TokenStre
                     s => return Err(meta.error(format!("Invalid storage type `{s}`, expected `Table` or `SparseSet`."))),
                 };
                 Ok(())
+            } else if let Some(hook) = ["on_add", "on_insert", "on_replace", "on_remove"].into_iter().find(|&hook| meta.path.is_ident(hook)) {
+                let path = meta.value()?.parse::<Path>()?;
+                hooks.push((meta.path, hook, path));
+                Ok(())
             } else {
                 Err(meta.error("Unsupported `Component` attribute"))
             })?;
         }
 
+        let mut target_type = None;
+        for meta in input.attrs.iter().filter(|&attr| attr.path().is_ident("relationship")) {
+            meta.parse_nested_meta(|meta| if meta.path.is_ident("target") {
+                target_type = Some(meta.value()?.parse::<Path>()?);
+                Ok(())
+            } else {
+                Err(meta.error("Unsupported `relationship` attribute"))
+            })?;
+        }
+
+        let mut relationship_type = None;
+        for meta in input.attrs.iter().filter(|&attr| attr.path().is_ident("relationship_target")) {
+            meta.parse_nested_meta(|meta| if meta.path.is_ident("relationship") {
+                relationship_type = Some(meta.value()?.parse::<Path>()?);
+                Ok(())
+            } else {
+                Err(meta.error("Unsupported `relationship_target` attribute"))
+            })?;
+        }
+
+        // Relationship components maintain both sides through their hooks, which may not be overridden.
+        let reserved = match (&target_type, &relationship_type) {
+            (Some(..), Some(..)) => return Err(Error::new_spanned(&input, "Components may not be both a relationship and a relationship target.")),
+            (Some(..), None) => &["on_insert", "on_replace", "on_remove"][..],
+            (None, Some(..)) => &["on_remove"][..],
+            (None, None) => &[][..],
+        };
+
+        if let Some((path, hook, ..)) = hooks.iter().find(|(_, hook, ..)| reserved.contains(hook)) {
+            return Err(Error::new_spanned(path, format!("Relationship components may not override `{hook}`.")));
+        }
+
         let storage = {
             let storage = Ident::new(&storage, Span::call_site());
             quote! { #fei_ecs::component::ComponentStorage::#storage }
@@ -48,10 +85,77 @@ pub fn derive_component(input: proc_macro::TokenStream) -> proc_macro::TokenStre
         let target = &input.ident;
         let (impl_generics, type_generics, where_clause) = &input.generics.split_for_impl();
 
+        let field = (target_type.is_some() || relationship_type.is_some()).then(|| match &input.data {
+            Data::Struct(data) if data.fields.len() == 1 => Ok(data.fields.iter().next().unwrap().ident
+                .as_ref()
+                .map(ToTokens::to_token_stream)
+                .unwrap_or_else(|| Index { index: 0, span: Span::call_site(), }.into_token_stream())
+            ),
+            _ => Err(Error::new_spanned(&input, "Relationship components must be `struct`s with exactly 1 field.")),
+        }).transpose()?;
+
+        let mut hooks = hooks.into_iter().map(|(_, hook, path)| {
+            let hook = Ident::new(hook, Span::call_site());
+            quote! { hooks.#hook(#path); }
+        }).collect::<Vec<_>>();
+
+        let relation = if let Some(target_type) = target_type {
+            hooks.insert(0, quote! { #fei_ecs::relationship::relationship_hooks::<Self>(hooks); });
+            quote! {
+                impl #impl_generics #fei_ecs::relationship::Relationship for #target #type_generics #where_clause {
+                    type Target = #target_type;
+
+                    #[inline]
+                    fn get(&self) -> #fei_ecs::entity::Entity {
+                        self.#field
+                    }
+
+                    #[inline]
+                    fn from_entity(entity: #fei_ecs::entity::Entity) -> Self {
+                        Self { #field: entity, }
+                    }
+                }
+            }
+        } else if let Some(relationship_type) = relationship_type {
+            hooks.insert(0, quote! { #fei_ecs::relationship::relationship_target_hooks::<Self>(hooks); });
+            quote! {
+                impl #impl_generics #fei_ecs::relationship::RelationshipTarget for #target #type_generics #where_clause {
+                    type Relationship = #relationship_type;
+
+                    #[inline]
+                    fn sources(&self) -> &[#fei_ecs::entity::Entity] {
+                        &self.#field
+                    }
+
+                    #[inline]
+                    fn sources_mut(&mut self) -> &mut Vec<#fei_ecs::entity::Entity> {
+                        &mut self.#field
+                    }
+
+                    #[inline]
+                    fn from_sources(sources: Vec<#fei_ecs::entity::Entity>) -> Self {
+                        Self { #field: sources, }
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let register_hooks = (!hooks.is_empty()).then(|| quote! {
+            #[inline]
+            fn register_hooks(hooks: &mut #fei_ecs::component::ComponentHooks) {
+                #(#hooks)*
+            }
+        });
+
         Ok(quote! {
             impl #impl_generics #fei_ecs::component::Component for #target #type_generics #where_clause {
                 const STORAGE: #fei_ecs::component::ComponentStorage = #storage;
+                #register_hooks
             }
+
+            #relation
         })
     })() {
         Ok(stream) => stream,
@@ -136,6 +240,52 @@ pub fn derive_component_set(input: proc_macro::TokenStream) -> proc_macro::Token
     }.into()
 }
 
+#[proc_macro_derive(Event)]
+pub fn derive_event(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match (move || -> syn::Result<TokenStream> {
+        let mut input = syn::parse::<DeriveInput>(input)?;
+        let fei_ecs = fei_macros::module("fei-ecs")?.ok_or_else(|| Error::new_spanned(&input, "`fei-ecs` is unavailable."))?;
+
+        input.generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse2(quote! { Self: 'static + Send + Sync + Sized })?);
+
+        let target = &input.ident;
+        let (impl_generics, type_generics, where_clause) = &input.generics.split_for_impl();
+
+        Ok(quote! {
+            impl #impl_generics #fei_ecs::event::Event for #target #type_generics #where_clause {}
+        })
+    })() {
+        Ok(stream) => stream,
+        Err(e) => e.to_compile_error(),
+    }.into()
+}
+
+#[proc_macro_derive(SystemSet)]
+pub fn derive_system_set(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match (move || -> syn::Result<TokenStream> {
+        let mut input = syn::parse::<DeriveInput>(input)?;
+        let fei_ecs = fei_macros::module("fei-ecs")?.ok_or_else(|| Error::new_spanned(&input, "`fei-ecs` is unavailable."))?;
+
+        input.generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse2(quote! { Self: 'static + Send + Sync + std::fmt::Debug + Clone + Eq + std::hash::Hash })?);
+
+        let target = &input.ident;
+        let (impl_generics, type_generics, where_clause) = &input.generics.split_for_impl();
+
+        Ok(quote! {
+            impl #impl_generics #fei_ecs::schedule::SystemSet for #target #type_generics #where_clause {}
+        })
+    })() {
+        Ok(stream) => stream,
+        Err(e) => e.to_compile_error(),
+    }.into()
+}
+
 #[proc_macro_derive(Resource)]
 pub fn derive_resource(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     match derive_resource_generic(input, false) {
@@ -0,0 +1,15 @@
+use fei_common::prelude::*;
+use crate::world::World;
+
+/// A deferred operation on the [`World`], recorded into a [`CommandQueue`](crate::command::CommandQueue)
+/// and applied later at a sync point, where exclusive access to the world is available.
+pub trait Command: 'static + Send + Sized {
+    fn apply(self, world: &mut World) -> anyhow::Result<()>;
+}
+
+impl<Func: FnOnce(&mut World) -> anyhow::Result<()> + 'static + Send> Command for Func {
+    #[inline]
+    fn apply(self, world: &mut World) -> anyhow::Result<()> {
+        (self)(world)
+    }
+}
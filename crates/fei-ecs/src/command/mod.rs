@@ -0,0 +1,147 @@
+mod def;
+mod param;
+mod queue;
+
+pub use def::*;
+pub use param::*;
+pub use queue::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_common::prelude::*;
+    use fei_ecs_macros::{
+        Component, Resource,
+    };
+    use crate::{
+        entity::Entity,
+        query::{
+            Query, With,
+        },
+        resource::Res,
+        system::{
+            IntoSystem, System,
+        },
+        world::World,
+    };
+    use std::sync::{
+        Arc,
+        atomic::{
+            AtomicUsize, Ordering,
+        },
+    };
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Health(u32);
+    #[derive(Component, Debug, PartialEq)]
+    #[component(storage = "SparseSet")]
+    struct Poisoned;
+    #[derive(Resource)]
+    struct Spawned(usize);
+
+    #[test]
+    fn system_param() -> anyhow::Result<()> {
+        fn spawner(mut commands: Commands) -> anyhow::Result<Entity> {
+            let entity = commands.spawn(Health(3))?.insert(Poisoned).id();
+            let batch = commands.spawn_batch((0..4).map(Health))?;
+            commands.insert_res(Spawned(batch.len() + 1));
+            Ok(entity)
+        }
+
+        fn poison(mut commands: Commands, query: Query<(Entity, &Health), With<Poisoned>>) -> anyhow::Result<()> {
+            for (entity, health) in &query {
+                let mut entity = commands.entity(entity);
+                if health.0 > 1 {
+                    entity.insert(Health(health.0 - 1));
+                } else {
+                    entity.despawn();
+                }
+            }
+
+            Ok(())
+        }
+
+        fn count(query: Query<&Health>, spawned: Res<Spawned>) -> anyhow::Result<(usize, usize)> {
+            Ok((query.iter().count(), spawned.0))
+        }
+
+        let mut world = World::default();
+        let mut spawner = spawner.into_system(&mut world)?;
+        let mut poison = poison.into_system(&mut world)?;
+        let mut count = count.into_system(&mut world)?;
+
+        let entity = spawner.call((), &mut world)?;
+        assert_eq!(count.call((), &mut world)?, (5, 5));
+        assert_eq!(world.view(entity)?.get::<Health>().as_deref(), Some(&Health(3)));
+
+        poison.call((), &mut world)?;
+        poison.call((), &mut world)?;
+        assert_eq!(world.view(entity)?.get::<Health>().as_deref(), Some(&Health(1)));
+
+        poison.call((), &mut world)?;
+        assert!(world.view(entity).is_err());
+        assert_eq!(count.call((), &mut world)?, (4, 5));
+        Ok(())
+    }
+
+    #[test]
+    fn queue() -> anyhow::Result<()> {
+        struct Tracked(Arc<AtomicUsize>);
+        impl Command for Tracked {
+            #[inline]
+            fn apply(self, _: &mut World) -> anyhow::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl Drop for Tracked {
+            #[inline]
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        struct Unit;
+        impl Command for Unit {
+            #[inline]
+            fn apply(self, world: &mut World) -> anyhow::Result<()> {
+                world.spawn(Health(0))?;
+                Ok(())
+            }
+        }
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let mut world = World::default();
+        let mut queue = CommandQueue::default();
+
+        // Commands of mixed sizes and alignments, stored back-to-back.
+        queue.push(Unit);
+        queue.push(Tracked(dropped.clone()));
+        queue.push(|world: &mut World| {
+            world.spawn(Health(1))?;
+            Ok(())
+        });
+        queue.push(Unit);
+        assert_eq!(queue.len(), 4);
+
+        queue.apply(&mut world)?;
+        assert!(queue.is_empty());
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+        assert_eq!(world.query::<&Health>().iter(&world).count(), 3);
+
+        // Commands after a failing one are dropped without being applied.
+        let mut other = CommandQueue::default();
+        other.push(|_: &mut World| Err(anyhow::anyhow!("failed")));
+        other.push(Tracked(dropped.clone()));
+        queue.append(&mut other);
+        assert!(other.is_empty());
+        assert!(queue.apply(&mut world).is_err());
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+
+        // Pending commands are dropped along with the queue.
+        queue.push(Tracked(dropped.clone()));
+        drop(queue);
+        assert_eq!(dropped.load(Ordering::Relaxed), 3);
+        Ok(())
+    }
+}
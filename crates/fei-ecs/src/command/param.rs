@@ -0,0 +1,229 @@
+use fei_common::prelude::*;
+use crate::{
+    command::{
+        Command, CommandQueue,
+    },
+    component::ComponentSet,
+    entity::{
+        Entity,
+        Entities, ReserveError,
+    },
+    event::Event,
+    hierarchy::Parent,
+    resource::Resource,
+    system::{
+        SystemParam, ReadOnlySystemParam,
+        Access,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeMark,
+};
+
+/// A [`SystemParam`] recording operations on the [`World`] to be applied later, when the system's
+/// deferred state is [applied](SystemParam::apply). Entities spawned through it are
+/// [reserved](Entities::reserve) up-front, so their IDs are usable right away.
+pub struct Commands<'w, 's> {
+    entities: &'w Entities,
+    queue: &'s mut CommandQueue,
+}
+
+impl<'w, 's> Commands<'w, 's> {
+    #[inline]
+    pub fn new(entities: &'w Entities, queue: &'s mut CommandQueue) -> Self {
+        Self { entities, queue, }
+    }
+
+    /// Pushes a custom command to the queue.
+    #[inline]
+    pub fn add<C: Command>(&mut self, command: C) {
+        self.queue.push(command);
+    }
+
+    /// Reserves an entity without any components.
+    #[inline]
+    pub fn spawn_empty(&mut self) -> Result<EntityCommands<'_, 'w, 's>, ReserveError> {
+        let entity = self.entities.reserve()?;
+        Ok(EntityCommands {
+            entity,
+            commands: self,
+        })
+    }
+
+    /// Reserves an entity and queues the insertion of `set` into it.
+    #[inline]
+    pub fn spawn<T: ComponentSet>(&mut self, set: T) -> Result<EntityCommands<'_, 'w, 's>, ReserveError> {
+        let mut commands = self.spawn_empty()?;
+        commands.insert(set);
+        Ok(commands)
+    }
+
+    /// Reserves an entity for each set and queues their insertion, returning the reserved entities.
+    pub fn spawn_batch<T: ComponentSet>(&mut self, sets: impl IntoIterator<Item = T>) -> Result<Vec<Entity>, ReserveError> {
+        let sets = sets.into_iter().collect::<Vec<_>>();
+        let entities = self.entities.reserve_many(sets.len())?.collect::<Vec<_>>();
+
+        let spawned = entities.clone();
+        self.add(move |world: &mut World| {
+            for (entity, set) in spawned.into_iter().zip(sets) {
+                world.view_mut(entity)?.insert(set);
+            }
+            Ok(())
+        });
+
+        Ok(entities)
+    }
+
+    /// Returns an [`EntityCommands`] for queueing operations on `entity`. Whether the entity exists
+    /// is only checked once the commands are applied.
+    #[inline]
+    pub fn entity(&mut self, entity: Entity) -> EntityCommands<'_, 'w, 's> {
+        EntityCommands {
+            entity,
+            commands: self,
+        }
+    }
+
+    #[inline]
+    pub fn insert_res<T: Resource>(&mut self, resource: T) {
+        self.add(move |world: &mut World| {
+            world.insert_res(resource);
+            Ok(())
+        });
+    }
+
+    #[inline]
+    pub fn remove_res<T: Resource>(&mut self) {
+        self.add(|world: &mut World| {
+            world.remove_res::<T>();
+            Ok(())
+        });
+    }
+
+    /// Queues a global [trigger](World::trigger) of `event`.
+    #[inline]
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        self.add(move |world: &mut World| world.trigger(event));
+    }
+
+    /// Queues a [trigger](World::trigger_targets) of `event` for each of the given entities.
+    #[inline]
+    pub fn trigger_targets<E: Event>(&mut self, event: E, targets: impl IntoIterator<Item = Entity>) {
+        let targets = targets.into_iter().collect::<Vec<_>>();
+        self.add(move |world: &mut World| world.trigger_targets(event, targets));
+    }
+}
+
+unsafe impl<'w, 's> ReadOnlySystemParam for Commands<'w, 's> {}
+impl<'w, 's> SystemParam for Commands<'w, 's> {
+    type State = CommandQueue;
+    type Item<'w2, 's2> = Commands<'w2, 's2>;
+    type ReadOnly = Self;
+
+    #[inline]
+    unsafe fn construct<'w2, 's2>(world: WorldCell<'w2>, state: &'s2 mut Self::State, _: ChangeMark, _: ChangeMark) -> anyhow::Result<Self::Item<'w2, 's2>> {
+        Ok(Commands::new(world.entities(), state))
+    }
+
+    #[inline]
+    fn construct_state(_: &mut World, _: &mut Access) -> anyhow::Result<Self::State> {
+        Ok(default())
+    }
+
+    #[inline]
+    fn apply(state: &mut Self::State, world: &mut World) -> anyhow::Result<()> {
+        state.apply(world)
+    }
+}
+
+/// Queues operations on a single entity, obtained from [`Commands`].
+pub struct EntityCommands<'a, 'w, 's> {
+    entity: Entity,
+    commands: &'a mut Commands<'w, 's>,
+}
+
+impl<'a, 'w, 's> EntityCommands<'a, 'w, 's> {
+    #[inline]
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    #[inline]
+    pub fn insert<T: ComponentSet>(&mut self, set: T) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world: &mut World| {
+            world.view_mut(entity)?.insert(set);
+            Ok(())
+        });
+        self
+    }
+
+    #[inline]
+    pub fn remove<T: ComponentSet>(&mut self) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world: &mut World| {
+            world.view_mut(entity)?.remove::<T>();
+            Ok(())
+        });
+        self
+    }
+
+    /// Queues a [trigger](World::trigger_targets) of `event` for this entity.
+    #[inline]
+    pub fn trigger<E: Event>(&mut self, event: E) -> &mut Self {
+        self.commands.trigger_targets(event, [self.entity]);
+        self
+    }
+
+    /// Queues [setting](crate::world::EntityViewMut::set_parent) the parent of this entity.
+    #[inline]
+    pub fn set_parent(&mut self, parent: Entity) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world: &mut World| {
+            world.view_mut(entity)?.set_parent(parent)?;
+            Ok(())
+        });
+        self
+    }
+
+    #[inline]
+    pub fn remove_parent(&mut self) -> &mut Self {
+        self.remove::<Parent>()
+    }
+
+    /// Queues [adding](crate::world::EntityViewMut::add_child) a child to this entity.
+    #[inline]
+    pub fn add_child(&mut self, child: Entity) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world: &mut World| {
+            world.view_mut(entity)?.add_child(child)?;
+            Ok(())
+        });
+        self
+    }
+
+    /// Queues [removing](crate::world::EntityViewMut::remove_child) a child from this entity.
+    #[inline]
+    pub fn remove_child(&mut self, child: Entity) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world: &mut World| {
+            world.view_mut(entity)?.remove_child(child)?;
+            Ok(())
+        });
+        self
+    }
+
+    #[inline]
+    pub fn despawn(self) {
+        let entity = self.entity;
+        self.commands.add(move |world: &mut World| Ok(world.despawn(entity)?));
+    }
+
+    /// Queues despawning this entity along with all of its descendants.
+    #[inline]
+    pub fn despawn_recursive(self) {
+        let entity = self.entity;
+        self.commands.add(move |world: &mut World| Ok(world.despawn_recursive(entity)?));
+    }
+}
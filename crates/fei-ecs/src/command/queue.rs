@@ -0,0 +1,158 @@
+use fei_common::{
+    prelude::*,
+    ptr::{
+        PtrMut, PtrOwned,
+    },
+};
+use crate::{
+    command::Command,
+    world::World,
+};
+use std::{
+    mem::{
+        self,
+        MaybeUninit,
+    },
+    ptr::NonNull,
+};
+
+/// A type-erased list of [`Command`]s, stored back-to-back in a byte buffer.
+pub struct CommandQueue {
+    bytes: VecErased<'static>,
+    metas: Vec<CommandMeta>,
+}
+
+struct CommandMeta {
+    offset: usize,
+    apply: unsafe fn(PtrOwned, &mut World) -> anyhow::Result<()>,
+    drop: unsafe fn(PtrOwned),
+}
+
+// Safety: Commands are `Send`, and the queue is only ever accessed through a mutable reference.
+unsafe impl Send for CommandQueue {}
+unsafe impl Sync for CommandQueue {}
+
+impl Default for CommandQueue {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            bytes: VecErased::typed::<MaybeUninit<u8>>(),
+            metas: Vec::new(),
+        }
+    }
+}
+
+impl CommandQueue {
+    /// Returns the number of commands in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.metas.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.metas.is_empty()
+    }
+
+    /// Pushes a command to the back of the queue.
+    pub fn push<C: Command>(&mut self, command: C) {
+        let offset = self.bytes.len();
+        let size = mem::size_of::<C>();
+
+        if size > 0 {
+            self.bytes.reserve(size);
+            // Safety:
+            // - The buffer has just been reserved, and `MaybeUninit<u8>` needs no initialization.
+            // - The command's bytes are copied as-is, so alignment doesn't matter.
+            unsafe {
+                self.bytes.set_len(offset + size);
+                PtrOwned::take(command, |ptr| self.bytes.get_unchecked_mut(offset).write(ptr, size));
+            }
+        } else {
+            mem::forget(command);
+        }
+
+        self.metas.push(CommandMeta {
+            offset,
+            apply: |ptr, world| unsafe { Self::read::<C>(ptr) }.apply(world),
+            drop: |ptr| drop(unsafe { Self::read::<C>(ptr) }),
+        });
+    }
+
+    /// Moves every command of `other` to the back of this queue, leaving `other` empty.
+    pub fn append(&mut self, other: &mut CommandQueue) {
+        let base = self.bytes.len();
+        let size = other.bytes.len();
+
+        if size > 0 {
+            self.bytes.reserve(size);
+            // Safety: Commands have been written as raw bytes, so moving them as such is fine.
+            unsafe {
+                self.bytes.set_len(base + size);
+                self.bytes.get_unchecked_mut(base).write(other.bytes.get_unchecked_mut(0).own(), size);
+                other.bytes.set_len(0);
+            }
+        }
+
+        self.metas.extend(other.metas.drain(..).map(|meta| CommandMeta {
+            offset: base + meta.offset,
+            ..meta
+        }));
+    }
+
+    /// Applies every command in order and clears the queue, [flushing](crate::entity::Entities::flush)
+    /// entity reservations beforehand. Commands queued by [component hooks](crate::component::ComponentHooks)
+    /// are applied after each command. If a command fails, the remaining ones are dropped without being
+    /// applied, and the error is returned.
+    pub fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        world.flush_entities();
+
+        let mut result = Ok(());
+        for meta in mem::take(&mut self.metas) {
+            let ptr = unsafe { self.command_ptr(meta.offset) };
+            if result.is_ok() {
+                result = unsafe { (meta.apply)(ptr, world) }.and_then(|_| world.apply_commands());
+            } else {
+                unsafe { (meta.drop)(ptr) };
+            }
+        }
+
+        // Safety: Every command has been moved out of the buffer.
+        unsafe { self.bytes.set_len(0) };
+        result
+    }
+
+    /// Returns an owning pointer to the command starting at `offset`, which may be unaligned.
+    ///
+    /// # Safety
+    /// `offset` must be the offset of a command in the queue, and the returned pointer must be
+    /// consumed with [`read`](CommandQueue::read) exactly once.
+    #[inline]
+    unsafe fn command_ptr(&mut self, offset: usize) -> PtrOwned<'static> {
+        if offset < self.bytes.len() {
+            self.bytes.get_unchecked_mut(offset).own()
+        } else {
+            // Zero-sized commands at the end of the buffer have no bytes to point to.
+            PtrOwned::new(NonNull::dangling())
+        }
+    }
+
+    /// Reads a possibly unaligned command from the buffer.
+    ///
+    /// # Safety
+    /// `ptr` must point to an instance of `C` written by [`push`](CommandQueue::push).
+    #[inline]
+    unsafe fn read<C: Command>(ptr: PtrOwned) -> C {
+        let mut command = MaybeUninit::<C>::uninit();
+        PtrMut::from(&mut command).write(ptr, mem::size_of::<C>());
+        command.assume_init()
+    }
+}
+
+impl Drop for CommandQueue {
+    fn drop(&mut self) {
+        for meta in mem::take(&mut self.metas) {
+            unsafe { (meta.drop)(self.command_ptr(meta.offset)) };
+        }
+    }
+}
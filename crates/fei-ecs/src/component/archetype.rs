@@ -10,29 +10,37 @@ use crate::{
         ComponentSetId, ComponentSetInfo,
     },
     entity::Entity,
+    ChangeMark,
 };
 use fixedbitset::FixedBitSet;
-use std::ptr::{
-    self,
-    NonNull,
+use std::{
+    cell::UnsafeCell,
+    ptr::{
+        self,
+        NonNull,
+    },
 };
 
-pub(super) struct Archetype {
-    pub component_bits: FixedBitSet,
-    pub sparse_set_components: Box<[ComponentId]>,
-    pub zst_components: Box<[ComponentId]>,
+/// A unique set of components, shared by every [`Entity`] that holds exactly that set.
+pub struct Archetype {
+    pub(crate) component_bits: FixedBitSet,
+    pub(crate) sparse_set_components: Box<[ComponentId]>,
+    pub(crate) zst_components: Box<[ComponentId]>,
 
-    pub table_id: Option<TableId>,
+    pub(crate) table_id: Option<TableId>,
+    /// All entities residing in this archetype, indexed by [`EntityLocation::archetype_index`](
+    /// crate::entity::EntityLocation).
+    pub(crate) entities: Vec<Entity>,
 
     /// Always has a target ID: same ID as self if the inserter is a subset, new ID otherwise.
-    pub insertions: SparseSet<ComponentSetId, ArchetypeId>,
+    pub(crate) insertions: SparseSet<ComponentSetId, ArchetypeId>,
     /// [`None`] if the remover is a superset, [`Some`] of the same ID if the remover is disjoint,
     /// and [`Some`] of a new ID otherwise.
-    pub removals: SparseSet<ComponentSetId, Option<ArchetypeId>>,
+    pub(crate) removals: SparseSet<ComponentSetId, Option<ArchetypeId>>,
 }
 
 impl Archetype {
-    pub unsafe fn new(
+    pub(crate) unsafe fn new(
         component_bits: FixedBitSet,
         components: &[ComponentId], mut get_info: impl FnMut(ComponentId) -> ComponentInfo,
         mut get_table: impl FnMut(&[ComponentId]) -> TableId,
@@ -57,15 +65,48 @@ impl Archetype {
             zst_components: zst_components.into_boxed_slice(),
 
             table_id: (!table_components.is_empty()).then(|| get_table(&table_components)),
+            entities: Vec::new(),
 
             insertions: SparseSet::new(),
             removals: SparseSet::new(),
         }
     }
+
+    /// Returns the bitset of all component IDs this archetype holds.
+    #[inline]
+    pub fn component_bits(&self) -> &FixedBitSet {
+        &self.component_bits
+    }
+
+    /// Returns the table this archetype stores its table components in, if any.
+    #[inline]
+    pub fn table_id(&self) -> Option<TableId> {
+        self.table_id
+    }
+
+    /// Returns all entities residing in this archetype.
+    #[inline]
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    #[inline]
+    #[must_use = "use the returned value as the entity's archetypal index"]
+    pub(crate) fn insert(&mut self, entity: Entity) -> usize {
+        self.entities.push(entity);
+        self.entities.len() - 1
+    }
+
+    #[inline]
+    #[must_use = "use the returned value as the swapped entity's archetypal index"]
+    pub(crate) fn remove(&mut self, index: usize) -> Option<Entity> {
+        self.entities.swap_remove(index);
+        self.entities.get(index).copied()
+    }
 }
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub(crate) struct ArchetypeId(pub usize);
+pub struct ArchetypeId(pub(crate) usize);
 impl SparseIndex for ArchetypeId {
     #[inline]
     fn into_index(self) -> usize {
@@ -78,22 +119,64 @@ impl SparseIndex for ArchetypeId {
     }
 }
 
-pub(super) struct Table {
+/// Tracks which archetypes have been created since a point in time. Archetypes are never removed, so
+/// archetypes created after a generation are exactly the ones indexed from it onwards.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct ArchetypeGeneration(pub(crate) usize);
+
+/// Change marks of a single component value, stored next to it.
+pub struct ComponentTicks {
+    pub(crate) added: UnsafeCell<ChangeMark>,
+    pub(crate) updated: UnsafeCell<ChangeMark>,
+}
+
+impl ComponentTicks {
+    #[inline]
+    pub fn new(mark: ChangeMark) -> Self {
+        Self {
+            added: UnsafeCell::new(mark),
+            updated: UnsafeCell::new(mark),
+        }
+    }
+
+    /// Returns the mark of when the component was added.
+    #[inline]
+    pub fn added(&self) -> ChangeMark {
+        unsafe { *self.added.get() }
+    }
+
+    /// Returns the mark of when the component was last mutably accessed or overwritten.
+    #[inline]
+    pub fn updated(&self) -> ChangeMark {
+        unsafe { *self.updated.get() }
+    }
+
+    #[inline]
+    pub(crate) fn set_updated(&mut self, mark: ChangeMark) {
+        *self.updated.get_mut() = mark;
+    }
+}
+
+/// A structure of arrays holding table-stored components of every entity whose archetype refers to it.
+pub struct Table {
     components: Box<[ComponentId]>,
-    pub component_bits: FixedBitSet,
+    pub(crate) component_bits: FixedBitSet,
     entities: Vec<Entity>,
     columns: SparseSet<ComponentId, VecErased<'static>>,
+    ticks: SparseSet<ComponentId, Vec<ComponentTicks>>,
 }
 
 impl Table {
-    pub unsafe fn new(components: &[ComponentId], mut get_info: impl FnMut(ComponentId) -> ComponentInfo) -> Self {
+    pub(crate) unsafe fn new(components: &[ComponentId], mut get_info: impl FnMut(ComponentId) -> ComponentInfo) -> Self {
         let id_len = components.last().unwrap_unchecked().0 + 1;
         let mut columns = SparseSet::with_capacity(id_len);
+        let mut ticks = SparseSet::with_capacity(id_len);
         let mut component_bits = FixedBitSet::with_capacity(id_len);
 
         for &id in components {
             let info = get_info(id);
             columns.insert(id, VecErased::new(info.layout(), info.dropper().into()));
+            ticks.insert(id, Vec::new());
             component_bits.insert(id.0);
         }
 
@@ -102,9 +185,35 @@ impl Table {
             component_bits,
             entities: Vec::new(),
             columns,
+            ticks,
         }
     }
 
+    /// Returns all entities residing in this table, indexed by their table index.
+    #[inline]
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Returns the column storing components of the given ID.
+    ///
+    /// # Safety
+    /// The table must contain the component ID.
+    #[inline]
+    pub unsafe fn column(&self, id: ComponentId) -> &VecErased<'static> {
+        self.columns.get_unchecked(id)
+    }
+
+    /// Returns the change marks of components of the given ID, indexed the same way as
+    /// [`column`](Table::column).
+    ///
+    /// # Safety
+    /// The table must contain the component ID.
+    #[inline]
+    pub unsafe fn ticks(&self, id: ComponentId) -> &[ComponentTicks] {
+        self.ticks.get_unchecked(id)
+    }
+
     #[inline]
     pub unsafe fn get(&self, index: usize, id: ComponentId) -> Ptr {
         self.columns
@@ -113,55 +222,78 @@ impl Table {
     }
 
     #[inline]
-    pub unsafe fn get_mut(&mut self, index: usize, id: ComponentId) -> PtrMut {
+    pub(crate) unsafe fn get_mut(&mut self, index: usize, id: ComponentId) -> PtrMut {
         self.columns
             .get_unchecked_mut(id)
             .get_unchecked_mut(index)
     }
 
+    /// Like [`get_mut`](Table::get_mut), also returning the component's change marks.
+    #[inline]
+    pub(crate) unsafe fn get_mut_with_ticks(&mut self, index: usize, id: ComponentId) -> (PtrMut<'_>, &ComponentTicks) {
+        (
+            self.columns.get_unchecked_mut(id).get_unchecked_mut(index),
+            self.ticks.get_unchecked(id).get_unchecked(index),
+        )
+    }
+
     #[inline]
     #[must_use = "use the returned value as the entity's archetypal index"]
-    pub unsafe fn insert(&mut self, entity: Entity, set: PtrOwned<'static>, set_info: &ComponentSetInfo) -> usize {
+    pub(crate) unsafe fn insert(&mut self, entity: Entity, set: PtrOwned<'static>, set_info: &ComponentSetInfo, current: ChangeMark) -> usize {
         self.entities.push(entity);
         for &id in &*self.components {
             self.columns
                 .get_unchecked_mut(id)
                 .push(ptr::read(&set).byte_add(*set_info.component_offsets.get_unchecked(id)));
+            self.ticks
+                .get_unchecked_mut(id)
+                .push(ComponentTicks::new(current));
         }
 
         self.entities.len() - 1
     }
 
     #[inline]
-    pub unsafe fn update(&mut self, index: usize, set: PtrOwned<'static>, set_info: &ComponentSetInfo) {
+    pub(crate) unsafe fn update(&mut self, index: usize, set: PtrOwned<'static>, set_info: &ComponentSetInfo, current: ChangeMark) {
         for &id in &*set_info.table_components {
             self.columns
                 .get_unchecked_mut(id)
                 .set_unchecked(index, ptr::read(&set).byte_add(*set_info.component_offsets.get_unchecked(id)));
+            self.ticks
+                .get_unchecked_mut(id)
+                .get_unchecked_mut(index)
+                .set_updated(current);
         }
     }
 
     #[inline]
     #[must_use = "use the returned value as the entity's archetypal index"]
-    pub unsafe fn insert_from(
+    pub(crate) unsafe fn insert_from(
         &mut self,
         from: &mut Self, from_index: usize,
         set: PtrOwned<'static>, set_info: &ComponentSetInfo,
+        current: ChangeMark,
     ) -> (Option<Entity>, usize) {
         let entity = from.entities.swap_remove(from_index);
         self.entities.push(entity);
 
         for &id in &*self.components {
             let to = self.columns.get_unchecked_mut(id);
-            if let Some(from) = from.columns.get_mut(id) {
+            let to_ticks = self.ticks.get_unchecked_mut(id);
+            if let Some(from_column) = from.columns.get_mut(id) {
+                let mut ticks = from.ticks.get_unchecked_mut(id).swap_remove(from_index);
                 if let Some(&offset) = set_info.component_offsets.get(id) {
-                    from.swap_remove_unchecked_and_drop(from_index);
+                    from_column.swap_remove_unchecked_and_drop(from_index);
                     to.push(ptr::read(&set).byte_add(offset));
+                    ticks.set_updated(current);
                 } else {
-                    from.swap_remove_unchecked(from_index, |ptr| to.push(ptr));
+                    from_column.swap_remove_unchecked(from_index, |ptr| to.push(ptr));
                 }
+
+                to_ticks.push(ticks);
             } else {
                 to.push(ptr::read(&set).byte_add(*set_info.component_offsets.get_unchecked(id)));
+                to_ticks.push(ComponentTicks::new(current));
             }
         }
 
@@ -170,7 +302,7 @@ impl Table {
 
     #[inline]
     #[must_use = "use the returned value as the entity's archetypal index"]
-    pub unsafe fn remove_from(
+    pub(crate) unsafe fn remove_from(
         &mut self,
         from: &mut Self, from_index: usize,
     ) -> (Option<Entity>, usize) {
@@ -178,9 +310,11 @@ impl Table {
         self.entities.push(entity);
 
         for &id in &*from.components {
+            let ticks = from.ticks.get_unchecked_mut(id).swap_remove(from_index);
             let from = from.columns.get_unchecked_mut(id);
             if let Some(to) = self.columns.get_mut(id) {
                 from.swap_remove_unchecked(from_index, |ptr| to.push(ptr));
+                self.ticks.get_unchecked_mut(id).push(ticks);
             } else {
                 from.swap_remove_unchecked_and_drop(from_index);
             }
@@ -191,7 +325,7 @@ impl Table {
 
     #[inline]
     #[must_use = "use the returned value as the entity's archetypal index"]
-    pub unsafe fn extract_from(
+    pub(crate) unsafe fn extract_from(
         &mut self,
         from: &mut Self, from_index: usize,
         mut extract: impl FnMut(ComponentId, PtrOwned<'static>),
@@ -200,10 +334,12 @@ impl Table {
         self.entities.push(entity);
 
         for &id in &*from.components {
+            let ticks = from.ticks.get_unchecked_mut(id).swap_remove(from_index);
             from.columns
                 .get_unchecked_mut(id)
                 .swap_remove_unchecked(from_index, |ptr| if let Some(to) = self.columns.get_mut(id) {
                     to.push(ptr);
+                    self.ticks.get_unchecked_mut(id).push(ticks);
                 } else {
                     extract(id, ptr);
                 });
@@ -214,10 +350,11 @@ impl Table {
 
     #[inline]
     #[must_use = "use the returned value as the entity's archetypal index"]
-    pub unsafe fn remove(&mut self, index: usize) -> Option<Entity> {
+    pub(crate) unsafe fn remove(&mut self, index: usize) -> Option<Entity> {
         self.entities.swap_remove(index);
         for &id in &*self.components {
             self.columns.get_unchecked_mut(id).swap_remove_unchecked_and_drop(index);
+            self.ticks.get_unchecked_mut(id).swap_remove(index);
         }
 
         self.entities.get(index).copied()
@@ -225,10 +362,11 @@ impl Table {
 
     #[inline]
     #[must_use = "use the returned value as the entity's archetypal index"]
-    pub unsafe fn extract(&mut self, index: usize, mut extract: impl FnMut(ComponentId, PtrOwned<'static>)) -> Option<Entity> {
+    pub(crate) unsafe fn extract(&mut self, index: usize, mut extract: impl FnMut(ComponentId, PtrOwned<'static>)) -> Option<Entity> {
         self.entities.swap_remove(index);
         for &id in &*self.components {
             self.columns.get_unchecked_mut(id).swap_remove_unchecked(index, |ptr| extract(id, ptr));
+            self.ticks.get_unchecked_mut(id).swap_remove(index);
         }
 
         self.entities.get(index).copied()
@@ -236,7 +374,7 @@ impl Table {
 }
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub(super) struct TableId(pub usize);
+pub struct TableId(pub(crate) usize);
 impl SparseIndex for TableId {
     #[inline]
     fn into_index(self) -> usize {
@@ -250,14 +388,16 @@ impl SparseIndex for TableId {
 }
 
 #[derive(Default)]
-pub(super) struct SparseSets {
+pub(crate) struct SparseSets {
     sets: SparseSet<ComponentId, SparseSetErased<'static, u32>>,
+    ticks: SparseSet<ComponentId, SparseSet<u32, ComponentTicks>>,
 }
 
 impl SparseSets {
     #[inline]
     pub fn init(&mut self, id: ComponentId, info: ComponentInfo) {
         self.sets.insert(id, unsafe { SparseSetErased::new(info.layout(), info.dropper()) });
+        self.ticks.insert(id, SparseSet::new());
     }
 
     #[inline]
@@ -265,6 +405,16 @@ impl SparseSets {
         self.sets.get_unchecked(id).contains(entity.id())
     }
 
+    #[inline]
+    pub unsafe fn set(&self, id: ComponentId) -> &SparseSetErased<'static, u32> {
+        self.sets.get_unchecked(id)
+    }
+
+    #[inline]
+    pub unsafe fn ticks(&self, id: ComponentId) -> &SparseSet<u32, ComponentTicks> {
+        self.ticks.get_unchecked(id)
+    }
+
     #[inline]
     pub unsafe fn get(&self, entity: Entity, id: ComponentId) -> Ptr {
         self.sets.get_unchecked(id).get_unchecked(entity.id())
@@ -276,12 +426,21 @@ impl SparseSets {
     }
 
     #[inline]
-    pub unsafe fn insert(&mut self, entity: Entity, set: PtrOwned<'static>, set_info: &ComponentSetInfo) {
+    pub unsafe fn get_mut_with_ticks(&mut self, entity: Entity, id: ComponentId) -> (PtrMut<'_>, &ComponentTicks) {
+        (
+            self.sets.get_unchecked_mut(id).get_unchecked_mut(entity.id()),
+            self.ticks.get_unchecked(id).get_unchecked(entity.id()),
+        )
+    }
+
+    #[inline]
+    pub unsafe fn insert(&mut self, entity: Entity, set: PtrOwned<'static>, set_info: &ComponentSetInfo, current: ChangeMark) {
         let index = entity.id();
         for &id in &*set_info.sparse_set_components {
             self.sets
                 .get_unchecked_mut(id)
                 .insert_and_drop(index, ptr::read(&set).byte_add(*set_info.component_offsets.get_unchecked(id)));
+            insert_ticks(self.ticks.get_unchecked_mut(id), index, current);
         }
     }
 
@@ -291,6 +450,7 @@ impl SparseSets {
         for &id in components {
             let Some(set) = self.sets.get_mut(id) else { continue };
             set.remove_and_drop(index);
+            self.ticks.get_unchecked_mut(id).remove(index);
         }
     }
 
@@ -301,39 +461,56 @@ impl SparseSets {
             self.sets
                 .get_unchecked_mut(id)
                 .remove(index, |ptr| extract(id, ptr));
+            self.ticks.get_unchecked_mut(id).remove(index);
         }
     }
 }
 
 #[derive(Default)]
-pub(super) struct Bitset {
-    sets: SparseSet<ComponentId, (FixedBitSet, Option<unsafe fn(*mut u8)>)>,
+pub(crate) struct Bitset {
+    sets: SparseSet<ComponentId, BitColumn>,
+}
+
+struct BitColumn {
+    bits: FixedBitSet,
+    ticks: SparseSet<u32, ComponentTicks>,
+    dropper: Option<unsafe fn(*mut u8)>,
 }
 
 impl Bitset {
     #[inline]
     pub fn init(&mut self, id: ComponentId, dropper: Option<unsafe fn(*mut u8)>) {
-        self.sets.insert(id, (FixedBitSet::new(), dropper));
+        self.sets.insert(id, BitColumn {
+            bits: FixedBitSet::new(),
+            ticks: SparseSet::new(),
+            dropper,
+        });
     }
 
     #[inline]
     pub unsafe fn contains(&self, entity: Entity, id: ComponentId) -> bool {
-        let (set, ..) = self.sets.get_unchecked(id);
-        set.contains(entity.id() as usize)
+        self.sets.get_unchecked(id).bits.contains(entity.id() as usize)
+    }
+
+    #[inline]
+    pub unsafe fn ticks(&self, id: ComponentId) -> &SparseSet<u32, ComponentTicks> {
+        &self.sets.get_unchecked(id).ticks
     }
 
     #[inline]
-    pub unsafe fn insert(&mut self, entity: Entity, set_info: &ComponentSetInfo) {
+    pub unsafe fn insert(&mut self, entity: Entity, set_info: &ComponentSetInfo, current: ChangeMark) {
         let index = entity.id() as usize;
         for &id in &*set_info.zst_components {
-            let (set, dropper) = self.sets.get_unchecked_mut(id);
-            set.grow(index + 1);
+            let BitColumn { bits, ticks, dropper } = self.sets.get_unchecked_mut(id);
+            bits.grow(index + 1);
 
-            if set.put(index) {
+            if bits.put(index) {
                 if let Some(dropper) = *dropper {
                     dropper(NonNull::<()>::dangling().cast::<u8>().as_ptr());
                 }
             }
+
+            insert_ticks(ticks, entity.id(), current);
         }
     }
 
@@ -341,9 +518,10 @@ impl Bitset {
     pub unsafe fn remove(&mut self, entity: Entity, components: &[ComponentId]) {
         let index = entity.id() as usize;
         for &id in components {
-            let Some((set, dropper)) = self.sets.get_mut(id) else { continue };
-            if set.contains(index) {
-                set.set(index, false);
+            let Some(BitColumn { bits, ticks, dropper }) = self.sets.get_mut(id) else { continue };
+            if bits.contains(index) {
+                bits.set(index, false);
+                ticks.remove(entity.id());
                 if let Some(dropper) = *dropper {
                     dropper(NonNull::<()>::dangling().cast::<u8>().as_ptr());
                 }
@@ -355,8 +533,9 @@ impl Bitset {
     pub unsafe fn extract(&mut self, entity: Entity, components: &[ComponentId]) {
         let index = entity.id() as usize;
         for &id in components {
-            let (set, ..) = self.sets.get_unchecked_mut(id);
-            set.set(index, false);
+            let BitColumn { bits, ticks, .. } = self.sets.get_unchecked_mut(id);
+            bits.set(index, false);
+            ticks.remove(entity.id());
         }
     }
 }
@@ -364,12 +543,22 @@ impl Bitset {
 impl Drop for Bitset {
     #[inline]
     fn drop(&mut self) {
-        for (set, dropper) in self.sets.iter_sparse() {
+        for BitColumn { bits, dropper, .. } in self.sets.iter_sparse() {
             if let Some(dropper) = *dropper {
-                for _ in 0..set.count_ones(..) {
+                for _ in 0..bits.count_ones(..) {
                     unsafe { dropper(NonNull::<()>::dangling().cast::<u8>().as_ptr()) };
                 }
             }
         }
     }
 }
+
+/// Marks a component as updated if it's already present, or as added otherwise.
+#[inline]
+fn insert_ticks(ticks: &mut SparseSet<u32, ComponentTicks>, index: u32, current: ChangeMark) {
+    if let Some(ticks) = ticks.get_mut(index) {
+        ticks.set_updated(current);
+    } else {
+        ticks.insert(index, ComponentTicks::new(current));
+    }
+}
@@ -9,13 +9,15 @@ use crate::{
         Entity, Entities, EntityLocation,
     },
     component::{
-        Component, ComponentId, ComponentInfo, ComponentStorage,
-        ComponentSet, ComponentSetId, ComponentSetInfo,
-        Archetype, ArchetypeId, Table, TableId, Bitset, SparseSets,
+        Component, ComponentId, ComponentInfo, ComponentDescriptor, ComponentStorage, ComponentHooks,
+        ComponentSet, ComponentSetId, ComponentSetInfo, ComponentTicks,
+        Archetype, ArchetypeId, ArchetypeGeneration, Table, TableId, Bitset, SparseSets,
     },
+    ChangeMark,
 };
 use fixedbitset::FixedBitSet;
 use std::{
+    alloc::Layout,
     any::TypeId,
     borrow::Cow,
     mem::MaybeUninit,
@@ -43,6 +45,7 @@ pub struct Components {
 
     component_set_info: Vec<ComponentSetInfo>,
     component_set_ids: FxHashMap<TypeId, ComponentSetId>,
+    dynamic_set_ids: FxHashMap<Box<[ComponentId]>, (ComponentSetId, Layout)>,
 }
 
 unsafe impl Send for Components {}
@@ -68,6 +71,17 @@ impl Components {
         )
     }
 
+    /// Registers a component described at runtime. Unlike [`register`](Components::register), each
+    /// call registers a new component that isn't tied to any [`TypeId`].
+    #[inline]
+    pub fn register_descriptor(&mut self, descriptor: ComponentDescriptor) -> ComponentId {
+        Self::push_info(
+            &mut self.bitsets, &mut self.sparse_sets,
+            &mut self.component_info,
+            ComponentInfo::from_descriptor(descriptor),
+        )
+    }
+
     unsafe fn register_impl(
         bitsets: &mut Bitset,
         sparse_sets: &mut SparseSets,
@@ -75,19 +89,27 @@ impl Components {
         component_ids: &mut FxHashMap<TypeId, ComponentId>,
         type_id: TypeId, info: ComponentInfo,
     ) -> ComponentId {
-        *component_ids.entry(type_id).or_insert_with(|| {
-            component_info.reserve_exact(1);
-            component_info.push(info);
-
-            let id = ComponentId(component_info.len() - 1);
-            match info.storage() {
-                Some(ComponentStorage::Table) => {},
-                Some(ComponentStorage::SparseSet) => sparse_sets.init(id, info),
-                None => bitsets.init(id, info.dropper()),
-            }
+        *component_ids.entry(type_id).or_insert_with(|| Self::push_info(bitsets, sparse_sets, component_info, info))
+    }
 
-            id
-        })
+    fn push_info(
+        bitsets: &mut Bitset,
+        sparse_sets: &mut SparseSets,
+        component_info: &mut Vec<ComponentInfo>,
+        mut info: ComponentInfo,
+    ) -> ComponentId {
+        info.register();
+        component_info.reserve_exact(1);
+        component_info.push(info);
+
+        let id = ComponentId(component_info.len() - 1);
+        match info.storage() {
+            Some(ComponentStorage::Table) => {},
+            Some(ComponentStorage::SparseSet) => sparse_sets.init(id, info),
+            None => bitsets.init(id, info.dropper()),
+        }
+
+        id
     }
 
     #[inline]
@@ -95,6 +117,50 @@ impl Components {
         self.component_ids.get(&TypeId::of::<T>()).copied()
     }
 
+    #[inline]
+    pub fn get_info(&self, id: ComponentId) -> Option<&ComponentInfo> {
+        self.component_info.get(id.0)
+    }
+
+    /// Returns the lifecycle hooks of a component for modification.
+    #[inline]
+    pub fn hooks_mut(&mut self, id: ComponentId) -> Option<&mut ComponentHooks> {
+        self.component_info.get_mut(id.0).map(|info| &mut info.hooks)
+    }
+
+    #[inline]
+    pub fn get_set_info(&self, id: ComponentSetId) -> Option<&ComponentSetInfo> {
+        self.component_set_info.get(id.0)
+    }
+
+    /// Returns all archetypes, indexed by their [`ArchetypeId`].
+    #[inline]
+    pub fn archetypes(&self) -> &[Archetype] {
+        &self.archetypes
+    }
+
+    /// Returns the current archetype generation, advanced whenever a new archetype is created.
+    #[inline]
+    pub fn archetype_generation(&self) -> ArchetypeGeneration {
+        ArchetypeGeneration(self.archetypes.len())
+    }
+
+    /// Returns all tables, indexed by their [`TableId`].
+    #[inline]
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    #[inline]
+    pub(crate) fn sparse_sets(&self) -> &SparseSets {
+        &self.sparse_sets
+    }
+
+    #[inline]
+    pub(crate) fn bitsets(&self) -> &Bitset {
+        &self.bitsets
+    }
+
     pub fn register_set<T: ComponentSet>(&mut self) -> ComponentSetId {
         *self.component_set_ids.entry(TypeId::of::<T>()).or_insert_with(|| {
             let set_info = ComponentSetInfo::new::<T>(|type_id, component_info| unsafe {
@@ -111,14 +177,38 @@ impl Components {
         })
     }
 
+    /// Registers a set of the given components, laid out in the given order as if they were fields
+    /// of a `#[repr(C)]` struct. Returns the set along with its layout, or `None` if `components` is
+    /// empty or holds unregistered components.
+    ///
+    /// # Panics
+    /// Panics if any component is given more than once.
+    pub fn register_dynamic_set(&mut self, components: &[ComponentId]) -> Option<(ComponentSetId, Layout)> {
+        if let Some(&set) = self.dynamic_set_ids.get(components) {
+            return Some(set)
+        }
+
+        let infos = components.iter()
+            .map(|&id| self.component_info.get(id.0).map(|&info| (id, info)))
+            .collect::<Option<Vec<_>>>()?;
+
+        let (set_info, layout) = ComponentSetInfo::from_components(infos)?;
+        self.component_set_info.reserve_exact(1);
+        self.component_set_info.push(set_info);
+
+        let set = (ComponentSetId(self.component_set_info.len() - 1), layout);
+        self.dynamic_set_ids.insert(components.into(), set);
+        Some(set)
+    }
+
     pub unsafe fn contains(&self, entity: Entity, location: EntityLocation, id: ComponentId) -> bool {
         let info = *self.component_info.get_unchecked(id.0);
         match info.storage() {
             Some(ComponentStorage::SparseSet) => self.sparse_sets.contains(entity, id),
             Some(ComponentStorage::Table) => {
+                // Archetypes without table components have no table at all.
                 let arch = self.archetypes.get_unchecked(location.archetype_id.0);
-                let table = self.tables.get_unchecked(arch.table_id.unwrap_unchecked().0);
-                table.component_bits.contains(id.0)
+                arch.table_id.is_some_and(|table_id| self.tables.get_unchecked(table_id.0).component_bits.contains(id.0))
             },
             None => self.bitsets.contains(entity, id),
         }
@@ -137,6 +227,23 @@ impl Components {
         }
     }
 
+    /// Returns the change marks of a component the entity holds.
+    ///
+    /// # Safety
+    /// `location` must be the location of `entity`, which must hold the component of the given ID.
+    pub unsafe fn get_ticks(&self, entity: Entity, location: EntityLocation, id: ComponentId) -> &ComponentTicks {
+        let info = *self.component_info.get_unchecked(id.0);
+        match info.storage() {
+            Some(ComponentStorage::SparseSet) => self.sparse_sets.ticks(id).get_unchecked(entity.id()),
+            Some(ComponentStorage::Table) => {
+                let arch = self.archetypes.get_unchecked(location.archetype_id.0);
+                let table = self.tables.get_unchecked(arch.table_id.unwrap_unchecked().0);
+                table.ticks(id).get_unchecked(location.table_index.unwrap_unchecked())
+            },
+            None => self.bitsets.ticks(id).get_unchecked(entity.id()),
+        }
+    }
+
     pub unsafe fn get_mut(&mut self, entity: Entity, location: EntityLocation, id: ComponentId) -> PtrMut {
         let info = *self.component_info.get_unchecked(id.0);
         match info.storage() {
@@ -150,7 +257,24 @@ impl Components {
         }
     }
 
-    pub unsafe fn insert(&mut self, entity: Entity, entities: &mut Entities, set: PtrOwned<'static>, set_id: ComponentSetId) {
+    /// Returns a component the entity holds mutably, along with its change marks.
+    ///
+    /// # Safety
+    /// `location` must be the location of `entity`, which must hold the component of the given ID.
+    pub unsafe fn get_mut_with_ticks(&mut self, entity: Entity, location: EntityLocation, id: ComponentId) -> (PtrMut<'_>, &ComponentTicks) {
+        let info = *self.component_info.get_unchecked(id.0);
+        match info.storage() {
+            Some(ComponentStorage::SparseSet) => self.sparse_sets.get_mut_with_ticks(entity, id),
+            Some(ComponentStorage::Table) => {
+                let arch = self.archetypes.get_unchecked(location.archetype_id.0);
+                let table = self.tables.get_unchecked_mut(arch.table_id.unwrap_unchecked().0);
+                table.get_mut_with_ticks(location.table_index.unwrap_unchecked(), id)
+            },
+            None => (PtrMut::new(NonNull::dangling()), self.bitsets.ticks(id).get_unchecked(entity.id())),
+        }
+    }
+
+    pub unsafe fn insert(&mut self, entity: Entity, entities: &mut Entities, set: PtrOwned<'static>, set_id: ComponentSetId, current: ChangeMark) {
         let location = entities.location_mut(entity);
         let set_info = self.component_set_info.get_unchecked(set_id.0);
 
@@ -160,14 +284,18 @@ impl Components {
             &self.component_info, location, set_info, set_id,
         );
 
-        self.sparse_sets.insert(entity, ptr::read(&set), set_info);
-        self.bitsets.insert(entity, set_info);
+        self.sparse_sets.insert(entity, ptr::read(&set), set_info, current);
+        self.bitsets.insert(entity, set_info, current);
         if let Some(from_id) = from_id {
             let loc = location.as_mut().unwrap_unchecked();
             loc.archetype_id = to_id;
 
             if from_id != to_id {
                 let [from_arch, to_arch] = self.archetypes.many_unchecked_mut([from_id.0, to_id.0]);
+                let from_arch_index = loc.archetype_index;
+                let arch_swapped = from_arch.remove(from_arch_index);
+                loc.archetype_index = to_arch.insert(entity);
+
                 if let Some(to_table_id) = to_arch.table_id {
                     if let Some(from_table_id) = from_arch.table_id {
                         if from_table_id != to_table_id {
@@ -177,6 +305,7 @@ impl Components {
                             let (swapped, table_index) = to_table.insert_from(
                                 from_table, from_index,
                                 set, set_info,
+                                current,
                             );
 
                             loc.table_index = Some(table_index);
@@ -186,30 +315,36 @@ impl Components {
                             }
                         } else {
                             let table = self.tables.get_unchecked_mut(to_table_id.0);
-                            table.update(loc.table_index.unwrap_unchecked(), set, set_info);
+                            table.update(loc.table_index.unwrap_unchecked(), set, set_info, current);
                         }
                     } else {
                         let table = self.tables.get_unchecked_mut(to_table_id.0);
-                        loc.table_index = Some(table.insert(entity, set, set_info));
+                        loc.table_index = Some(table.insert(entity, set, set_info, current));
                     }
                 }
+
+                if let Some(swapped) = arch_swapped {
+                    let swapped_loc = entities.location_mut(swapped).as_mut().unwrap_unchecked();
+                    swapped_loc.archetype_index = from_arch_index;
+                }
             } else {
                 let arch = self.archetypes.get_unchecked_mut(from_id.0);
                 if let Some(table_id) = arch.table_id {
                     let table = self.tables.get_unchecked_mut(table_id.0);
-                    table.update(loc.table_index.unwrap_unchecked(), set, set_info);
+                    table.update(loc.table_index.unwrap_unchecked(), set, set_info, current);
                 }
             }
         } else {
             let arch = self.archetypes.get_unchecked_mut(to_id.0);
             let mut new_loc = EntityLocation {
                 archetype_id: to_id,
+                archetype_index: arch.insert(entity),
                 table_index: None,
             };
 
             if let Some(table_id) = arch.table_id {
                 let table = self.tables.get_unchecked_mut(table_id.0);
-                new_loc.table_index = Some(table.insert(entity, set, set_info));
+                new_loc.table_index = Some(table.insert(entity, set, set_info, current));
             }
 
             *location = Some(new_loc);
@@ -233,6 +368,10 @@ impl Components {
             loc.archetype_id = to_id;
             if from_id != to_id {
                 let [from_arch, to_arch] = self.archetypes.many_unchecked_mut([from_id.0, to_id.0]);
+                let from_arch_index = loc.archetype_index;
+                let arch_swapped = from_arch.remove(from_arch_index);
+                loc.archetype_index = to_arch.insert(entity);
+
                 if let Some(from_table_id) = from_arch.table_id {
                     let from_index = loc.table_index.unwrap_unchecked();
                     if let Some(to_table_id) = to_arch.table_id {
@@ -258,10 +397,20 @@ impl Components {
                         }
                     }
                 }
+
+                if let Some(swapped) = arch_swapped {
+                    let swapped_loc = entities.location_mut(swapped).as_mut().unwrap_unchecked();
+                    swapped_loc.archetype_index = from_arch_index;
+                }
             }
         } else {
             let loc = location.take().unwrap_unchecked();
             let arch = self.archetypes.get_unchecked_mut(from_id.0);
+            if let Some(swapped) = arch.remove(loc.archetype_index) {
+                let swapped_loc = entities.location_mut(swapped).as_mut().unwrap_unchecked();
+                swapped_loc.archetype_index = loc.archetype_index;
+            }
+
             if let Some(table_id) = arch.table_id {
                 let table = self.tables.get_unchecked_mut(table_id.0);
                 let index = loc.table_index.unwrap_unchecked();
@@ -302,6 +451,10 @@ impl Components {
             loc.archetype_id = to_id;
             if from_id != to_id {
                 let [from_arch, to_arch] = self.archetypes.many_unchecked_mut([from_id.0, to_id.0]);
+                let from_arch_index = loc.archetype_index;
+                let arch_swapped = from_arch.remove(from_arch_index);
+                loc.archetype_index = to_arch.insert(entity);
+
                 if let Some(from_table_id) = from_arch.table_id {
                     let from_index = loc.table_index.unwrap_unchecked();
                     if let Some(to_table_id) = to_arch.table_id {
@@ -336,10 +489,20 @@ impl Components {
                         }
                     }
                 }
+
+                if let Some(swapped) = arch_swapped {
+                    let swapped_loc = entities.location_mut(swapped).as_mut().unwrap_unchecked();
+                    swapped_loc.archetype_index = from_arch_index;
+                }
             }
         } else {
             let loc = location.take().unwrap_unchecked();
             let arch = self.archetypes.get_unchecked_mut(from_id.0);
+            if let Some(swapped) = arch.remove(loc.archetype_index) {
+                let swapped_loc = entities.location_mut(swapped).as_mut().unwrap_unchecked();
+                swapped_loc.archetype_index = loc.archetype_index;
+            }
+
             if let Some(table_id) = arch.table_id {
                 let table = self.tables.get_unchecked_mut(table_id.0);
                 let index = loc.table_index.unwrap_unchecked();
@@ -375,7 +538,11 @@ impl Components {
 
     pub unsafe fn clear(&mut self, entity: Entity, entities: &mut Entities) {
         let Some(loc) = entities.location_mut(entity).take() else { return };
-        let arch = self.archetypes.get_unchecked(loc.archetype_id.0);
+        let arch = self.archetypes.get_unchecked_mut(loc.archetype_id.0);
+        if let Some(swapped) = arch.remove(loc.archetype_index) {
+            let swapped_loc = entities.location_mut(swapped).as_mut().unwrap_unchecked();
+            swapped_loc.archetype_index = loc.archetype_index;
+        }
 
         self.sparse_sets.remove(entity, &arch.sparse_set_components);
         self.bitsets.remove(entity, &arch.zst_components);
@@ -590,25 +757,25 @@ mod tests {
 
         unsafe {
             println!("===> Insert table/'fei' to A");
-            PtrOwned::take(TableStored("fei".to_string()), |ptr| components.insert(a, &mut entities, ptr, tab_id));
+            PtrOwned::take(TableStored("fei".to_string()), |ptr| components.insert(a, &mut entities, ptr, tab_id, ChangeMark::default()));
             println!("===> Insert table/'is' to A");
-            PtrOwned::take(TableStored("is".to_string()), |ptr| components.insert(a, &mut entities, ptr, tab_id));
+            PtrOwned::take(TableStored("is".to_string()), |ptr| components.insert(a, &mut entities, ptr, tab_id, ChangeMark::default()));
 
             println!("===> Remove table from A");
             components.remove(a, &mut entities, tab_id);
             println!("===> Insert table/'short' to A");
-            PtrOwned::take(TableStored("short".to_string()), |ptr| components.insert(a, &mut entities, ptr, tab_id));
+            PtrOwned::take(TableStored("short".to_string()), |ptr| components.insert(a, &mut entities, ptr, tab_id, ChangeMark::default()));
 
             println!("===> Insert set/6.942 to A");
-            PtrOwned::take(SetStored(6.942), |ptr| components.insert(a, &mut entities, ptr, set_id));
+            PtrOwned::take(SetStored(6.942), |ptr| components.insert(a, &mut entities, ptr, set_id, ChangeMark::default()));
 
             println!("===> Insert bit to A");
-            components.insert(a, &mut entities, PtrOwned::new(NonNull::dangling()), bit_id);
+            components.insert(a, &mut entities, PtrOwned::new(NonNull::dangling()), bit_id, ChangeMark::default());
 
             println!("===> Insert table/'fei' to B");
-            PtrOwned::take(TableStored("fei".to_string()), |ptr| components.insert(b, &mut entities, ptr, tab_id));
+            PtrOwned::take(TableStored("fei".to_string()), |ptr| components.insert(b, &mut entities, ptr, tab_id, ChangeMark::default()));
             println!("===> Insert table/'is' to B");
-            PtrOwned::take(TableStored("is".to_string()), |ptr| components.insert(b, &mut entities, ptr, tab_id));
+            PtrOwned::take(TableStored("is".to_string()), |ptr| components.insert(b, &mut entities, ptr, tab_id, ChangeMark::default()));
 
             println!("===> Remove A");
             components.clear(a, &mut entities);
@@ -619,7 +786,7 @@ mod tests {
                 table: TableStored("short".to_string()),
                 set: SetStored(4.2),
                 bit: BitStored,
-            }, |ptr| components.insert(b, &mut entities, ptr, all_id));
+            }, |ptr| components.insert(b, &mut entities, ptr, all_id, ChangeMark::default()));
 
             println!("Extract (set, bit) from B, expecting success");
             assert!(components.extract_as::<(SetStored, BitStored)>(b, &mut entities).is_some());
@@ -674,9 +841,9 @@ mod tests {
 
         unsafe {
             println!("===> Insert Tab1, Tab2, Tab3");
-            PtrOwned::take(Tab1(0), |ptr| components.insert(a, &mut entities, ptr, tab1_id));
-            PtrOwned::take(Tab2(1), |ptr| components.insert(a, &mut entities, ptr, tab2_id));
-            PtrOwned::take(Tab3(2), |ptr| components.insert(a, &mut entities, ptr, tab3_id));
+            PtrOwned::take(Tab1(0), |ptr| components.insert(a, &mut entities, ptr, tab1_id, ChangeMark::default()));
+            PtrOwned::take(Tab2(1), |ptr| components.insert(a, &mut entities, ptr, tab2_id, ChangeMark::default()));
+            PtrOwned::take(Tab3(2), |ptr| components.insert(a, &mut entities, ptr, tab3_id, ChangeMark::default()));
 
             println!("===> Remove Tab1");
             components.remove(a, &mut entities, tab1_id);
@@ -684,7 +851,7 @@ mod tests {
             components.remove(a, &mut entities, tab2_id);
 
             println!("===> Insert Tab1");
-            PtrOwned::take(Tab1(4), |ptr| components.insert(a, &mut entities, ptr, tab1_id));
+            PtrOwned::take(Tab1(4), |ptr| components.insert(a, &mut entities, ptr, tab1_id, ChangeMark::default()));
         }
 
         println!("===> Finish");
@@ -2,6 +2,7 @@ use fei_common::{
     prelude::*,
     drop_for,
 };
+use crate::component::ComponentHooks;
 use fixedbitset::FixedBitSet;
 use std::{
     any::{
@@ -34,6 +35,10 @@ pub trait Component: 'static + Send + Sync + Sized {
     /// https://doc.rust-lang.org/nomicon/exotic-sizes.html#zero-sized-types-zsts), as the storages
     /// for those will always be bitsets indexed by [`crate::entity::Entity::id`].
     const STORAGE: ComponentStorage = ComponentStorage::Table;
+
+    /// Registers the lifecycle hooks of this component type, e.g. with `#[component(on_add = path)]`.
+    #[inline]
+    fn register_hooks(_: &mut ComponentHooks) {}
 }
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -50,23 +55,130 @@ impl SparseIndex for ComponentId {
     }
 }
 
+/// Describes a component type at runtime, e.g. one defined by a scripting layer, to be registered
+/// with [`Components::register_descriptor`](crate::component::Components::register_descriptor)
+/// without a Rust type backing it.
+#[derive(Copy, Clone, Debug)]
+pub struct ComponentDescriptor {
+    name: &'static str,
+    layout: Layout,
+    storage: ComponentStorage,
+    dropper: Option<unsafe fn(*mut u8)>,
+    cloner: Option<unsafe fn(*const u8, *mut u8)>,
+}
+
+impl ComponentDescriptor {
+    /// Describes a component that is neither dropped nor cloneable.
+    ///
+    /// # Safety
+    /// Values of the component must be safe to send and share across threads.
+    #[inline]
+    pub const unsafe fn new(name: &'static str, layout: Layout, storage: ComponentStorage) -> Self {
+        Self {
+            name,
+            layout,
+            storage,
+            dropper: None,
+            cloner: None,
+        }
+    }
+
+    /// Sets the function dropping values of the component in-place.
+    ///
+    /// # Safety
+    /// `dropper` must be safe to call with pointers to values of the component.
+    #[inline]
+    pub const unsafe fn with_dropper(mut self, dropper: unsafe fn(*mut u8)) -> Self {
+        self.dropper = Some(dropper);
+        self
+    }
+
+    /// Sets the function cloning values of the component from the first pointer into the second,
+    /// uninitialized one.
+    ///
+    /// # Safety
+    /// `cloner` must be safe to call with pointers to values of the component.
+    #[inline]
+    pub const unsafe fn with_cloner(mut self, cloner: unsafe fn(*const u8, *mut u8)) -> Self {
+        self.cloner = Some(cloner);
+        self
+    }
+
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    #[inline]
+    pub const fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct ComponentInfo {
+    name: &'static str,
+    type_name: Option<fn() -> &'static str>,
     layout: Layout,
     storage: ComponentStorage,
     dropper: Option<unsafe fn(*mut u8)>,
+    cloner: Option<unsafe fn(*const u8, *mut u8)>,
+    register_hooks: fn(&mut ComponentHooks),
+    pub(super) hooks: ComponentHooks,
 }
 
 impl ComponentInfo {
     #[inline]
     pub const fn new<T: Component>() -> Self {
         Self {
+            name: "",
+            type_name: Some(type_name::<T>),
             layout: Layout::new::<T>(),
             storage: T::STORAGE,
             dropper: drop_for::<T>(),
+            cloner: None,
+            register_hooks: T::register_hooks,
+            hooks: ComponentHooks {
+                on_add: None,
+                on_insert: None,
+                on_replace: None,
+                on_remove: None,
+            },
+        }
+    }
+
+    #[inline]
+    pub fn from_descriptor(descriptor: ComponentDescriptor) -> Self {
+        Self {
+            name: descriptor.name,
+            type_name: None,
+            layout: descriptor.layout,
+            storage: descriptor.storage,
+            dropper: descriptor.dropper,
+            cloner: descriptor.cloner,
+            register_hooks: |_| {},
+            hooks: default(),
         }
     }
 
+    /// Returns the name of the component type, used in diagnostics. It's only resolved for Rust types
+    /// once they're registered, since [`type_name`] isn't `const`.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Resolves the name of the component type, and registers the hooks it declares on top of the
+    /// ones already set.
+    #[inline]
+    pub(super) fn register(&mut self) {
+        if let Some(type_name) = self.type_name.take() {
+            self.name = type_name();
+        }
+
+        (self.register_hooks)(&mut self.hooks);
+    }
+
     #[inline]
     pub const fn is_zst(&self) -> bool {
         self.layout.size() == 0
@@ -90,6 +202,18 @@ impl ComponentInfo {
     pub const fn dropper(&self) -> Option<unsafe fn(*mut u8)> {
         self.dropper
     }
+
+    /// Returns the function cloning values of the component, which is only available for
+    /// components registered from a [`ComponentDescriptor`].
+    #[inline]
+    pub const fn cloner(&self) -> Option<unsafe fn(*const u8, *mut u8)> {
+        self.cloner
+    }
+
+    #[inline]
+    pub const fn hooks(&self) -> &ComponentHooks {
+        &self.hooks
+    }
 }
 
 pub unsafe trait ComponentSet: 'static + Send + Sync + Sized {
@@ -152,22 +276,58 @@ pub struct ComponentSetInfo {
 }
 
 impl ComponentSetInfo {
+    /// Returns the components of the set, sorted by their IDs.
+    #[inline]
+    pub fn components(&self) -> &[ComponentId] {
+        &self.components
+    }
+
+    /// Returns the offset of a component from the start of the set, if the set contains it.
+    #[inline]
+    pub fn offset(&self, id: ComponentId) -> Option<usize> {
+        self.component_offsets.get(id).copied()
+    }
+
     pub fn new<T: ComponentSet>(mut register_component: impl FnMut(TypeId, ComponentInfo) -> ComponentId) -> Self {
-        let mut offsets = Vec::new();
+        let mut metadata = Vec::new();
+        T::metadata(0, &mut |offset, type_id, info| {
+            metadata.push((offset, register_component(type_id, info), info));
+        });
+
+        Self::from_metadata(metadata, type_name::<T>())
+    }
+
+    /// Creates the info of a set of already-registered components, laid out in the given order.
+    /// Returns the info along with the layout of the whole set, or `None` if `components` is empty.
+    ///
+    /// # Panics
+    /// Panics if any component is given more than once.
+    pub fn from_components(components: impl IntoIterator<Item = (ComponentId, ComponentInfo)>) -> Option<(Self, Layout)> {
+        let mut layout = Layout::new::<()>();
+        let mut metadata = Vec::new();
+        for (id, info) in components {
+            let (extended, offset) = layout.extend(info.layout()).expect("component set is too large");
+            layout = extended;
+            metadata.push((offset, id, info));
+        }
+
+        (!metadata.is_empty()).then(|| (Self::from_metadata(metadata, "<dynamic>"), layout.pad_to_align()))
+    }
+
+    fn from_metadata(metadata: Vec<(usize, ComponentId, ComponentInfo)>, name: &str) -> Self {
+        let mut offsets = Vec::with_capacity(metadata.len());
         let mut table_components = Vec::new();
         let mut sparse_set_components = Vec::new();
         let mut zst_components = Vec::new();
 
-        T::metadata(0, &mut |offset, type_id, info| {
-            let id = register_component(type_id, info);
+        for (offset, id, info) in metadata {
             offsets.push((offset, id));
-
             match info.storage() {
                 Some(ComponentStorage::Table) => &mut table_components,
                 Some(ComponentStorage::SparseSet) => &mut sparse_set_components,
                 None => &mut zst_components,
             }.push(id);
-        });
+        }
 
         offsets.sort_unstable_by_key(|&(.., ComponentId(id))| id);
         sparse_set_components.sort_unstable();
@@ -180,12 +340,12 @@ impl ComponentSetInfo {
 
         for (offset, id) in offsets {
             #[fei_panic]
-            fn duplicate_error<T: ComponentSet>() -> ! {
-                panic!("duplicate component for set `{}`", type_name::<T>())
+            fn duplicate_error(name: &str) -> ! {
+                panic!("duplicate component for set `{name}`")
             }
 
             if component_offsets.insert(id, offset).is_some() {
-                duplicate_error::<T>()
+                duplicate_error(name)
             } else {
                 components.push(id);
                 component_bits.insert(id.0);
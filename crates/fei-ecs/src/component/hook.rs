@@ -0,0 +1,172 @@
+use crate::{
+    component::ComponentId,
+    entity::Entity,
+    world::DeferredWorld,
+};
+
+/// A function ran when a component's lifecycle event happens on an entity. Structural changes
+/// aren't possible inside hooks, but may be queued as [commands](DeferredWorld::commands).
+pub type ComponentHook = for<'w> fn(DeferredWorld<'w>, Entity, ComponentId);
+
+/// Lifecycle hooks of a component type, registered either with [`Component::register_hooks`](
+/// crate::component::Component::register_hooks) or at runtime with [`World::component_hooks_mut`](
+/// crate::world::World::component_hooks_mut).
+#[derive(Copy, Clone, Default, Debug)]
+pub struct ComponentHooks {
+    pub(crate) on_add: Option<ComponentHook>,
+    pub(crate) on_insert: Option<ComponentHook>,
+    pub(crate) on_replace: Option<ComponentHook>,
+    pub(crate) on_remove: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    /// Sets the hook ran after the component is inserted into an entity that didn't have it.
+    #[inline]
+    pub fn on_add(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_add = Some(hook);
+        self
+    }
+
+    /// Sets the hook ran after the component is inserted into an entity, regardless of whether the
+    /// entity already had it. Runs after [`on_add`](ComponentHooks::on_add).
+    #[inline]
+    pub fn on_insert(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_insert = Some(hook);
+        self
+    }
+
+    /// Sets the hook ran before the component is overwritten by an insertion, while the old value
+    /// is still readable.
+    #[inline]
+    pub fn on_replace(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_replace = Some(hook);
+        self
+    }
+
+    /// Sets the hook ran before the component is removed from an entity, either by removal,
+    /// extraction, or despawning, while the value is still readable.
+    #[inline]
+    pub fn on_remove(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_remove = Some(hook);
+        self
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.on_add.is_none() &&
+        self.on_insert.is_none() &&
+        self.on_replace.is_none() &&
+        self.on_remove.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_common::prelude::*;
+    use fei_ecs_macros::{
+        Component, Resource,
+    };
+    use crate::{
+        command::CommandQueue,
+        world::World,
+    };
+
+    #[derive(Component)]
+    #[component(on_add = on_add, on_insert = on_insert, on_replace = on_replace, on_remove = on_remove)]
+    struct Cell(u32);
+    #[derive(Component)]
+    struct Marker;
+    #[derive(Resource, Default)]
+    struct Log(Vec<(&'static str, u32)>);
+
+    fn record(mut world: DeferredWorld, entity: Entity, event: &'static str) {
+        let cell = world.view(entity).ok().and_then(|view| view.get::<Cell>().map(|cell| cell.0));
+        world.res_mut::<Log>().unwrap().0.push((event, cell.unwrap()));
+    }
+
+    fn on_add(world: DeferredWorld, entity: Entity, _: ComponentId) {
+        record(world, entity, "add")
+    }
+
+    fn on_insert(world: DeferredWorld, entity: Entity, _: ComponentId) {
+        record(world, entity, "insert")
+    }
+
+    fn on_replace(world: DeferredWorld, entity: Entity, _: ComponentId) {
+        record(world, entity, "replace")
+    }
+
+    fn on_remove(world: DeferredWorld, entity: Entity, _: ComponentId) {
+        record(world, entity, "remove")
+    }
+
+    #[inline]
+    fn take_log(world: &mut World) -> Vec<(&'static str, u32)> {
+        std::mem::take(&mut world.res_mut::<Log>().unwrap().0)
+    }
+
+    #[test]
+    fn hooks() -> anyhow::Result<()> {
+        let mut world = World::default();
+        world.init_res::<Log>();
+
+        let entity = world.spawn(Cell(1))?.id();
+        assert_eq!(take_log(&mut world), [("add", 1), ("insert", 1)]);
+
+        // Replaced components are still readable in `on_replace`.
+        world.view_mut(entity)?.insert((Cell(2), Marker));
+        assert_eq!(take_log(&mut world), [("replace", 1), ("insert", 2)]);
+
+        world.view_mut(entity)?.remove::<Cell>();
+        world.view_mut(entity)?.remove::<Cell>();
+        assert_eq!(take_log(&mut world), [("remove", 2)]);
+
+        let mut view = world.view_mut(entity)?;
+        view.insert(Cell(3));
+        assert_eq!(view.extract::<Cell>().map(|cell| cell.0), Some(3));
+        assert!(view.extract::<(Cell, Marker)>().is_none());
+        view.insert(Cell(4));
+        view.despawn();
+        assert_eq!(take_log(&mut world), [("add", 3), ("insert", 3), ("remove", 3), ("add", 4), ("insert", 4), ("remove", 4)]);
+        Ok(())
+    }
+
+    #[test]
+    fn deferred() -> anyhow::Result<()> {
+        let mut world = World::default();
+        world.init_res::<Log>();
+        world.component_hooks_mut::<Marker>().on_add(|mut world, entity, _| {
+            world.commands().entity(entity).insert(Cell(5));
+        });
+
+        // Structural changes are deferred until the change that ran the hook ends.
+        let entity = world.spawn(Marker)?.id();
+        assert!(world.view(entity)?.contains::<Cell>());
+        assert_eq!(take_log(&mut world), [("add", 5), ("insert", 5)]);
+
+        // Hooks may despawn the entity, and errors of their commands are kept for the next flush.
+        world.component_hooks_mut::<Marker>().on_remove(|mut world, entity, _| {
+            world.commands().entity(entity).despawn();
+            world.commands().add(|_: &mut World| Err(anyhow::anyhow!("failed")));
+        });
+
+        world.view_mut(entity)?.remove::<Marker>();
+        assert!(world.view(entity).is_err());
+        assert!(world.flush_commands().is_err());
+        world.flush_commands()?;
+        assert_eq!(take_log(&mut world), [("remove", 5)]);
+
+        // Command queues flush hook commands after each command.
+        let mut queue = CommandQueue::default();
+        queue.push(|world: &mut World| {
+            world.spawn(Marker)?;
+            Ok(())
+        });
+        queue.push(|world: &mut World| {
+            assert_eq!(take_log(world), [("add", 5), ("insert", 5)]);
+            Ok(())
+        });
+        queue.apply(&mut world)
+    }
+}
@@ -1,7 +1,11 @@
 mod archetype;
 mod collection;
 mod def;
+mod hook;
+mod removal;
 
-pub(crate) use archetype::*;
+pub use archetype::*;
 pub use collection::*;
 pub use def::*;
+pub use hook::*;
+pub use removal::*;
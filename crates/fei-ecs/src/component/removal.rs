@@ -0,0 +1,199 @@
+use fei_common::prelude::*;
+use crate::{
+    component::{
+        Component, ComponentId,
+    },
+    entity::Entity,
+    event::{
+        Event, Events,
+        EventCursor, EventIter,
+    },
+    system::{
+        SystemParam, ReadOnlySystemParam,
+        Access,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeMark,
+};
+use std::{
+    iter::Map,
+    marker::PhantomData,
+};
+
+/// An entity that lost a component, recorded in [`RemovedComponentEvents`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RemovedComponentEntity(pub Entity);
+impl Event for RemovedComponentEntity {}
+
+/// Entities that lost components through removal, extraction, or despawning, recorded per
+/// component in double-buffered [`Events`]. Every component is recorded, since readers may be
+/// initialized later; the buffers only stay bounded if [`World::update_removed_components`] is called
+/// every frame.
+#[derive(Default)]
+pub struct RemovedComponentEvents {
+    events: SparseSet<ComponentId, Events<RemovedComponentEntity>>,
+}
+
+impl RemovedComponentEvents {
+    #[inline]
+    pub fn get(&self, id: ComponentId) -> Option<&Events<RemovedComponentEntity>> {
+        self.events.get(id)
+    }
+
+    #[inline]
+    pub(crate) fn init(&mut self, id: ComponentId) {
+        if !self.events.contains(id) {
+            self.events.insert(id, default());
+        }
+    }
+
+    #[inline]
+    pub(crate) fn send(&mut self, id: ComponentId, entity: Entity) {
+        self.init(id);
+        unsafe { self.events.get_unchecked_mut(id) }.send(RemovedComponentEntity(entity));
+    }
+
+    /// [Updates](Events::update) the buffer of every component.
+    #[inline]
+    pub fn update(&mut self) {
+        for events in self.events.iter_sparse_mut() {
+            events.update();
+        }
+    }
+}
+
+/// Reads entities that lost the component `T` since the system last read them.
+pub struct RemovedComponents<'world, 'state, T: Component> {
+    events: &'world Events<RemovedComponentEntity>,
+    cursor: &'state mut EventCursor<RemovedComponentEntity>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+pub type RemovedIter<'a> = Map<EventIter<'a, RemovedComponentEntity>, fn(&RemovedComponentEntity) -> Entity>;
+
+impl<'world, 'state, T: Component> RemovedComponents<'world, 'state, T> {
+    /// Iterates over the unread entities, marking them as read.
+    #[inline]
+    pub fn read(&mut self) -> RemovedIter<'_> {
+        self.cursor.read(self.events).map(|removed| removed.0)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cursor.len(self.events)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cursor.is_empty(self.events)
+    }
+
+    /// Marks every entity as read without iterating over them.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.cursor.clear(self.events)
+    }
+}
+
+unsafe impl<'world, 'state, T: Component> ReadOnlySystemParam for RemovedComponents<'world, 'state, T> {}
+impl<'world, 'state, T: Component> SystemParam for RemovedComponents<'world, 'state, T> {
+    type State = (ComponentId, EventCursor<RemovedComponentEntity>);
+    type Item<'w, 's> = RemovedComponents<'w, 's, T>;
+    type ReadOnly = Self;
+
+    #[inline]
+    unsafe fn construct<'w, 's>(world: WorldCell<'w>, state: &'s mut Self::State, _: ChangeMark, _: ChangeMark) -> anyhow::Result<Self::Item<'w, 's>> {
+        let (id, cursor) = state;
+        Ok(RemovedComponents {
+            // Safety: The buffer has been initialized in `construct_state`.
+            events: world.get().removed_components().get(*id).unwrap_unchecked(),
+            cursor,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Removals are only recorded while the world is mutably borrowed, so no access is registered.
+    #[inline]
+    fn construct_state(world: &mut World, _: &mut Access) -> anyhow::Result<Self::State> {
+        let id = world.register_component::<T>();
+        world.init_removed_components(id);
+        Ok((id, default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_ecs_macros::Component;
+    use crate::system::{
+        IntoSystem, System,
+    };
+
+    #[derive(Component)]
+    struct A;
+    #[derive(Component)]
+    struct B;
+
+    #[test]
+    fn removed_components() -> anyhow::Result<()> {
+        fn removed(mut removed: RemovedComponents<B>) -> anyhow::Result<Vec<Entity>> {
+            Ok(removed.read().collect())
+        }
+
+        let mut world = World::default();
+        let mut first = removed.into_system(&mut world)?;
+        let mut second = removed.into_system(&mut world)?;
+
+        let a = world.spawn((A, B))?.id();
+        let b = world.spawn((A, B))?.id();
+        let c = world.spawn(B)?.id();
+        assert!(first.call((), &mut world)?.is_empty());
+
+        world.view_mut(a)?.remove::<B>();
+        world.view_mut(a)?.remove::<B>();
+        assert!(world.view_mut(b)?.extract::<(A, B)>().is_some());
+        assert!(world.view_mut(c)?.extract::<(A, B)>().is_none());
+        assert_eq!(first.call((), &mut world)?, [a, b]);
+
+        world.despawn(c)?;
+        world.despawn(a)?;
+        assert_eq!(first.call((), &mut world)?, [c]);
+
+        // Components nothing reads are recorded as well.
+        let id = world.register_component::<A>();
+        assert_eq!(world.removed_components().get(id).map(Events::len), Some(2));
+
+        // Removals only survive one update.
+        world.update_removed_components();
+        world.update_removed_components();
+        assert!(second.call((), &mut world)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn bounded() -> anyhow::Result<()> {
+        fn removed(mut removed: RemovedComponents<B>) -> anyhow::Result<usize> {
+            Ok(removed.read().count())
+        }
+
+        let mut world = World::default();
+        let mut removed = removed.into_system(&mut world)?;
+        let [a, b] = [world.register_component::<A>(), world.register_component::<B>()];
+
+        for _ in 0..100 {
+            for _ in 0..10 {
+                world.spawn((A, B))?.despawn();
+            }
+
+            assert_eq!(removed.call((), &mut world)?, 10);
+            world.update_removed_components();
+        }
+
+        // Updated buffers only keep the last frame of removals, whether they're read or not.
+        assert_eq!(world.removed_components().get(a).map(Events::len), Some(10));
+        assert_eq!(world.removed_components().get(b).map(Events::len), Some(10));
+        Ok(())
+    }
+}
@@ -15,8 +15,6 @@ use std::{
 pub enum SpawnError {
     #[error("too many entities")]
     TooMany,
-    #[error("entity reservations entities not flush()-ed yet")]
-    NotFlushed,
 }
 
 #[derive(Error, Debug)]
@@ -51,6 +49,7 @@ pub struct Entities {
 #[derive(Copy, Clone, Debug)]
 pub struct EntityLocation {
     pub(crate) archetype_id: ArchetypeId,
+    pub(crate) archetype_index: usize,
     pub(crate) table_index: Option<usize>,
 }
 
@@ -64,10 +63,10 @@ impl Entities {
             .is_some_and(|&index| entity.generation == index.generation)
     }
 
+    /// Allocates a new entity, [flush](Entities::flush)-ing pending reservations beforehand.
     pub fn spawn(&mut self) -> Result<Entity, SpawnError> {
-        if *self.reservoir.get_mut() != 0 {
-            Err(SpawnError::NotFlushed)
-        } else if Self::MAX <= self.all.len() {
+        self.flush();
+        if Self::MAX <= self.all.len() {
             Err(SpawnError::TooMany)
         } else {
             Ok(if let Some(entity) = self.free.pop_front() {
@@ -144,8 +143,11 @@ impl Entities {
         }
     }
 
-    /// Frees an entity, allowing it to be reused by subsequent [`reserve`](Entities::reserve).
+    /// Frees an entity, allowing it to be reused by subsequent [`reserve`](Entities::reserve). Pending
+    /// reservations are [flush](Entities::flush)-ed beforehand, as they may not account for the newly
+    /// freed entity.
     pub fn free(&mut self, entity: Entity) {
+        self.flush();
         if let Some(index) = self.all.get_mut(entity.id as usize) {
             if index.generation == entity.generation {
                 index.generation += 2;
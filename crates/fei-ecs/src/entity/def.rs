@@ -1,4 +1,4 @@
-#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct Entity {
     /// Collection identifier that this entity resides in.
     pub(super) id: u32,
@@ -0,0 +1,169 @@
+use fei_common::prelude::*;
+use crate::resource::{
+    Resource, ResMut,
+};
+use std::{
+    iter::Chain,
+    marker::PhantomData,
+    slice::Iter,
+};
+
+pub trait Event: 'static + Send + Sync + Sized {}
+
+/// A double-buffered queue of events of type `E`. Events are kept around for two
+/// [updates](Events::update), so readers running before and after the writer in a frame both get to
+/// see them; readers that don't run within that window miss them.
+pub struct Events<E: Event> {
+    front: EventBuffer<E>,
+    back: EventBuffer<E>,
+    count: usize,
+}
+
+struct EventBuffer<E: Event> {
+    events: Vec<E>,
+    start: usize,
+}
+
+impl<E: Event> Resource for Events<E> {}
+impl<E: Event> Default for Events<E> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            front: EventBuffer { events: Vec::new(), start: 0, },
+            back: EventBuffer { events: Vec::new(), start: 0, },
+            count: 0,
+        }
+    }
+}
+
+impl<E: Event> Events<E> {
+    #[inline]
+    pub fn send(&mut self, event: E) {
+        self.back.events.push(event);
+        self.count += 1;
+    }
+
+    #[inline]
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        let len = self.back.events.len();
+        self.back.events.extend(events);
+        self.count += self.back.events.len() - len;
+    }
+
+    /// Swaps the buffers, dropping the events sent before the previous update. Should be called
+    /// exactly once per frame, e.g. with [`update_events`].
+    #[inline]
+    pub fn update(&mut self) {
+        std::mem::swap(&mut self.front, &mut self.back);
+        self.back.events.clear();
+        self.back.start = self.count;
+    }
+
+    /// Drops every event in both buffers.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.front.events.clear();
+        self.back.events.clear();
+        self.front.start = self.count;
+        self.back.start = self.count;
+    }
+
+    /// Returns the number of events currently stored.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.front.events.len() + self.back.events.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a cursor that only reads events sent after this call.
+    #[inline]
+    pub fn cursor(&self) -> EventCursor<E> {
+        EventCursor {
+            last: self.count,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn unread(&self, last: usize) -> EventIter<'_, E> {
+        #[inline]
+        fn slice<E: Event>(buffer: &EventBuffer<E>, last: usize) -> &[E] {
+            &buffer.events[last.saturating_sub(buffer.start).min(buffer.events.len())..]
+        }
+
+        EventIter(slice(&self.front, last).iter().chain(slice(&self.back, last).iter()))
+    }
+}
+
+/// Keeps track of which events of type `E` have been read. Used by [`EventReader`](crate::event::EventReader)
+/// to give each system its own view of the [`Events`].
+pub struct EventCursor<E: Event> {
+    last: usize,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E: Event> Default for EventCursor<E> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            last: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E: Event> EventCursor<E> {
+    /// Iterates over the events not yet read by this cursor, marking them as read.
+    #[inline]
+    pub fn read<'a>(&mut self, events: &'a Events<E>) -> EventIter<'a, E> {
+        let iter = events.unread(self.last);
+        self.last = events.count;
+        iter
+    }
+
+    /// Returns the number of events not yet read by this cursor.
+    #[inline]
+    pub fn len(&self, events: &Events<E>) -> usize {
+        events.unread(self.last).len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self, events: &Events<E>) -> bool {
+        self.len(events) == 0
+    }
+
+    /// Marks every event as read without iterating over them.
+    #[inline]
+    pub fn clear(&mut self, events: &Events<E>) {
+        self.last = events.count;
+    }
+}
+
+pub struct EventIter<'a, E: Event>(Chain<Iter<'a, E>, Iter<'a, E>>);
+impl<'a, E: Event> Iterator for EventIter<'a, E> {
+    type Item = &'a E;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, E: Event> ExactSizeIterator for EventIter<'a, E> {}
+
+/// A system that [updates](Events::update) the [`Events`] of type `E`; add it to a schedule that runs
+/// once per frame.
+#[inline]
+pub fn update_events<E: Event>(mut events: ResMut<Events<E>>) -> anyhow::Result<()> {
+    events.update();
+    Ok(())
+}
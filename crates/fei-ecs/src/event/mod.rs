@@ -0,0 +1,90 @@
+mod def;
+mod param;
+
+pub use def::*;
+pub use param::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_common::prelude::*;
+    use fei_ecs_macros::Event;
+    use crate::{
+        system::{
+            IntoSystem, System, In,
+        },
+        world::World,
+    };
+
+    #[derive(Event, Debug, Copy, Clone, PartialEq)]
+    struct Damage(u32);
+
+    #[test]
+    fn events() {
+        let mut events = Events::<Damage>::default();
+        let mut early = events.cursor();
+
+        events.send(Damage(1));
+        let mut late = events.cursor();
+        events.send_batch([Damage(2), Damage(3)]);
+        assert_eq!(events.len(), 3);
+
+        assert_eq!(late.len(&events), 2);
+        assert_eq!(late.read(&events).copied().collect::<Vec<_>>(), [Damage(2), Damage(3)]);
+        assert!(late.is_empty(&events));
+
+        // Events survive one update...
+        events.update();
+        events.send(Damage(4));
+        assert_eq!(late.read(&events).copied().collect::<Vec<_>>(), [Damage(4)]);
+
+        // ...but not two.
+        events.update();
+        events.update();
+        assert!(events.is_empty());
+        assert_eq!(early.read(&events).count(), 0);
+
+        events.send(Damage(5));
+        events.clear();
+        assert_eq!(early.read(&events).count(), 0);
+    }
+
+    #[test]
+    fn system_param() -> anyhow::Result<()> {
+        fn writer(In(damage): In<Vec<u32>>, mut writer: EventWriter<Damage>) -> anyhow::Result<()> {
+            writer.send_batch(damage.into_iter().map(Damage));
+            Ok(())
+        }
+
+        fn reader(mut reader: EventReader<Damage>) -> anyhow::Result<Vec<u32>> {
+            Ok(reader.read().map(|damage| damage.0).collect())
+        }
+
+        let mut world = World::default();
+        assert!(reader.into_system(&mut world)?.call((), &mut world).is_err());
+
+        world.init_res::<Events<Damage>>();
+        let mut writer = writer.into_system(&mut world)?;
+        let mut first = reader.into_system(&mut world)?;
+        let mut second = reader.into_system(&mut world)?;
+        let mut update = update_events::<Damage>.into_system(&mut world)?;
+        assert!(!writer.access().is_compatible(first.access()));
+        assert!(first.access().is_compatible(second.access()));
+
+        writer.call(vec![1, 2], &mut world)?;
+        assert_eq!(first.call((), &mut world)?, [1, 2]);
+        assert_eq!(first.call((), &mut world)?, []);
+
+        update.call((), &mut world)?;
+        writer.call(vec![3], &mut world)?;
+        assert_eq!(first.call((), &mut world)?, [3]);
+        assert_eq!(second.call((), &mut world)?, [1, 2, 3]);
+
+        update.call((), &mut world)?;
+        update.call((), &mut world)?;
+        writer.call(vec![4], &mut world)?;
+        assert_eq!(first.call((), &mut world)?, [4]);
+        assert_eq!(second.call((), &mut world)?, [4]);
+        Ok(())
+    }
+}
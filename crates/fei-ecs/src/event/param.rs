@@ -0,0 +1,104 @@
+use fei_common::prelude::*;
+use crate::{
+    event::{
+        Event, Events,
+        EventCursor, EventIter,
+    },
+    resource::{
+        Res, ResMut, ResourceId,
+    },
+    system::{
+        SystemParam, ReadOnlySystemParam,
+        Access,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeMark,
+};
+
+/// Reads events of type `E` sent since the system last read them.
+pub struct EventReader<'world, 'state, E: Event> {
+    events: Res<'world, Events<E>>,
+    cursor: &'state mut EventCursor<E>,
+}
+
+impl<'world, 'state, E: Event> EventReader<'world, 'state, E> {
+    /// Iterates over the unread events, marking them as read.
+    #[inline]
+    pub fn read(&mut self) -> EventIter<'_, E> {
+        self.cursor.read(&self.events)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cursor.len(&self.events)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cursor.is_empty(&self.events)
+    }
+
+    /// Marks every event as read without iterating over them.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.cursor.clear(&self.events)
+    }
+}
+
+unsafe impl<'world, 'state, E: Event> ReadOnlySystemParam for EventReader<'world, 'state, E> {}
+impl<'world, 'state, E: Event> SystemParam for EventReader<'world, 'state, E> {
+    type State = (ResourceId, EventCursor<E>);
+    type Item<'w, 's> = EventReader<'w, 's, E>;
+    type ReadOnly = Self;
+
+    #[inline]
+    unsafe fn construct<'w, 's>(world: WorldCell<'w>, state: &'s mut Self::State, last: ChangeMark, current: ChangeMark) -> anyhow::Result<Self::Item<'w, 's>> {
+        let (id, cursor) = state;
+        Ok(EventReader {
+            events: Res::construct(world, id, last, current)?,
+            cursor,
+        })
+    }
+
+    #[inline]
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        Ok((Res::<Events<E>>::construct_state(world, access)?, default()))
+    }
+}
+
+/// Sends events of type `E`.
+pub struct EventWriter<'world, E: Event> {
+    events: ResMut<'world, Events<E>>,
+}
+
+impl<'world, E: Event> EventWriter<'world, E> {
+    #[inline]
+    pub fn send(&mut self, event: E) {
+        self.events.send(event)
+    }
+
+    #[inline]
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        self.events.send_batch(events)
+    }
+}
+
+impl<'world, E: Event> SystemParam for EventWriter<'world, E> {
+    type State = ResourceId;
+    type Item<'w, 's> = EventWriter<'w, E>;
+    type ReadOnly = Res<'world, Events<E>>;
+
+    #[inline]
+    unsafe fn construct<'w, 's>(world: WorldCell<'w>, state: &'s mut Self::State, last: ChangeMark, current: ChangeMark) -> anyhow::Result<Self::Item<'w, 's>> {
+        Ok(EventWriter {
+            events: ResMut::construct(world, state, last, current)?,
+        })
+    }
+
+    #[inline]
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        ResMut::<Events<E>>::construct_state(world, access)
+    }
+}
@@ -0,0 +1,58 @@
+use fei_common::prelude::*;
+use fei_ecs_macros::Component;
+use crate::{
+    entity::Entity,
+    world::NonexistentError,
+};
+use std::ops::Deref;
+
+#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HierarchyError {
+    #[error("entity does not exist")]
+    Nonexistent,
+    #[error("entity `{:?}` can't be its own ancestor", .0)]
+    Cycle(Entity),
+}
+
+impl From<NonexistentError> for HierarchyError {
+    #[inline]
+    fn from(_: NonexistentError) -> Self {
+        Self::Nonexistent
+    }
+}
+
+/// The parent of an entity, set with [`EntityViewMut::set_parent`](crate::world::EntityViewMut::set_parent).
+/// A [`Relationship`](crate::relationship::Relationship) targeting the [`Children`] of the parent.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+#[relationship(target = Children)]
+pub struct Parent(Entity);
+
+impl Parent {
+    #[inline]
+    pub fn get(self) -> Entity {
+        self.0
+    }
+}
+
+/// The children of an entity, in insertion order. Removing it or despawning the entity removes the
+/// [`Parent`] of its children; use [`despawn_recursive`](crate::world::EntityViewMut::despawn_recursive)
+/// to despawn them along instead.
+#[derive(Component, Debug, Clone, Default, Eq, PartialEq)]
+#[relationship_target(relationship = Parent)]
+pub struct Children(Vec<Entity>);
+
+impl Children {
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Entity> + ExactSizeIterator + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl Deref for Children {
+    type Target = [Entity];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,103 @@
+mod def;
+mod world;
+
+pub use def::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_common::prelude::*;
+    use fei_ecs_macros::Component;
+    use crate::{
+        entity::Entity,
+        command::CommandQueue,
+        world::World,
+    };
+
+    #[derive(Component)]
+    struct Node;
+
+    #[inline]
+    fn children(world: &World, entity: Entity) -> Vec<Entity> {
+        world.view(entity).ok()
+            .and_then(|view| view.get::<Children>())
+            .map_or_else(Vec::new, |children| children.iter().collect())
+    }
+
+    #[inline]
+    fn parent(world: &World, entity: Entity) -> Option<Entity> {
+        world.view(entity).ok()?.get::<Parent>().map(|parent| parent.get())
+    }
+
+    #[test]
+    fn hierarchy() -> anyhow::Result<()> {
+        let mut world = World::default();
+        let root = world.spawn(Node)?.id();
+        let [a, b, c] = [(); 3].map(|_| world.spawn(Node).unwrap().id());
+
+        world.view_mut(root)?.add_child(a)?.add_child(b)?;
+        world.view_mut(c)?.set_parent(a)?;
+        assert_eq!(children(&world, root), [a, b]);
+        assert_eq!(parent(&world, c), Some(a));
+        assert_eq!(world.ancestors(c).collect::<Vec<_>>(), [a, root]);
+        assert_eq!(world.descendants(root).collect::<Vec<_>>(), [a, c, b]);
+
+        // Entities can't be their own ancestors.
+        assert!(matches!(world.view_mut(root)?.set_parent(c), Err(HierarchyError::Cycle(..))));
+        assert!(matches!(world.view_mut(a)?.add_child(a), Err(HierarchyError::Cycle(..))));
+
+        // Reparenting moves the entity out of its previous parent's children.
+        world.view_mut(c)?.set_parent(b)?;
+        assert!(children(&world, a).is_empty());
+        assert_eq!(children(&world, b), [c]);
+
+        world.view_mut(a)?.remove_child(c)?;
+        assert_eq!(parent(&world, c), Some(b));
+        world.view_mut(b)?.remove_child(c)?;
+        assert_eq!(parent(&world, c), None);
+        assert!(children(&world, b).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn despawn() -> anyhow::Result<()> {
+        let mut world = World::default();
+        let root = world.spawn(Node)?.id();
+        let [a, b, c, d] = [(); 4].map(|_| world.spawn(Node).unwrap().id());
+        world.view_mut(root)?.add_child(a)?.add_child(b)?;
+        world.view_mut(a)?.add_child(c)?;
+        world.view_mut(b)?.add_child(d)?;
+
+        // Despawned children leave their parent's children.
+        world.despawn(d)?;
+        assert!(children(&world, b).is_empty());
+
+        world.despawn(b)?;
+        assert_eq!(children(&world, root), [a]);
+
+        world.despawn_recursive(a)?;
+        assert!(world.view(a).is_err() && world.view(c).is_err());
+        assert!(children(&world, root).is_empty());
+
+        // Commands can build and tear down hierarchies.
+        let mut queue = CommandQueue::default();
+        let mut commands = crate::command::Commands::new(world.entities(), &mut queue);
+        let child = commands.spawn(Node)?.set_parent(root).id();
+        commands.entity(root).despawn_recursive();
+        queue.apply(&mut world)?;
+        assert!(world.view(root).is_err() && world.view(child).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn orphans() -> anyhow::Result<()> {
+        let mut world = World::default();
+        let root = world.spawn(Node)?.id();
+        let child = world.spawn(Node)?.id();
+        world.view_mut(root)?.add_child(child)?;
+
+        world.despawn(root)?;
+        assert_eq!(parent(&world, child), None);
+        Ok(())
+    }
+}
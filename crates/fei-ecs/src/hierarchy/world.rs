@@ -0,0 +1,104 @@
+use crate::{
+    entity::Entity,
+    hierarchy::{
+        Parent, Children, HierarchyError,
+    },
+    relationship::{
+        Relationship,
+        Ancestors, Descendants,
+    },
+    world::{
+        World, EntityViewMut, NonexistentError,
+    },
+};
+
+impl World {
+    /// Iterates over the ancestors of an entity, from its parent up to the root.
+    #[inline]
+    pub fn ancestors(&self, entity: Entity) -> Ancestors<'_, Parent> {
+        Ancestors::new(self, entity)
+    }
+
+    /// Iterates over the descendants of an entity depth-first, visiting each entity before its
+    /// children.
+    #[inline]
+    pub fn descendants(&self, entity: Entity) -> Descendants<'_, Children> {
+        Descendants::new(self, entity)
+    }
+
+    /// Despawns the entity along with all of its descendants.
+    #[inline]
+    pub fn despawn_recursive(&mut self, entity: Entity) -> Result<(), NonexistentError> {
+        self.view_mut(entity)?.despawn_recursive();
+        Ok(())
+    }
+}
+
+impl<'a> EntityViewMut<'a> {
+    /// Sets the parent of this entity, moving it out of the children of its previous parent.
+    #[inline]
+    pub fn set_parent(&mut self, parent: Entity) -> Result<&mut Self, HierarchyError> {
+        let entity = self.id();
+        if self.get::<Parent>().is_some_and(|current| current.get() == parent) { return Ok(self) }
+
+        self.world_scope(|world| {
+            if parent == entity || world.ancestors(parent).any(|ancestor| ancestor == entity) {
+                return Err(HierarchyError::Cycle(entity))
+            }
+
+            // Ensure the children collection exists, so the parent hooks may update it in-place.
+            let mut view = world.view_mut(parent)?;
+            if !view.contains::<Children>() {
+                view.insert(Children::default());
+            }
+
+            Ok(())
+        })?;
+
+        self.insert(Parent::from_entity(parent));
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn remove_parent(&mut self) -> &mut Self {
+        self.remove::<Parent>();
+        self
+    }
+
+    /// Sets the parent of `child` to this entity.
+    #[inline]
+    pub fn add_child(&mut self, child: Entity) -> Result<&mut Self, HierarchyError> {
+        let entity = self.id();
+        self.world_scope(|world| world.view_mut(child)?.set_parent(entity).map(|_| ()))?;
+        Ok(self)
+    }
+
+    /// Removes the parent of `child` if it's this entity.
+    #[inline]
+    pub fn remove_child(&mut self, child: Entity) -> Result<&mut Self, NonexistentError> {
+        let entity = self.id();
+        self.world_scope(|world| {
+            let mut view = world.view_mut(child)?;
+            if view.get::<Parent>().is_some_and(|parent| parent.get() == entity) {
+                view.remove::<Parent>();
+            }
+
+            Ok::<_, NonexistentError>(())
+        })?;
+
+        Ok(self)
+    }
+
+    /// Despawns the entity along with all of its descendants.
+    #[inline]
+    pub fn despawn_recursive(mut self) {
+        let entity = self.id();
+        self.world_scope(|world| {
+            // Descendants are despawned before their ancestors, so none of them get orphaned.
+            let descendants = world.descendants(entity).collect::<Vec<_>>();
+            world.despawn_many(descendants.into_iter().rev());
+        });
+
+        self.despawn();
+    }
+}
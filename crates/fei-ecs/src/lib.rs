@@ -2,7 +2,14 @@ pub use fei_ecs_macros;
 
 pub mod entity;
 pub mod component;
+pub mod query;
+pub mod command;
+pub mod event;
+pub mod hierarchy;
+pub mod observer;
+pub mod relationship;
 pub mod resource;
+pub mod schedule;
 pub mod system;
 pub mod world;
 
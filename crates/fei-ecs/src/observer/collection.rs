@@ -0,0 +1,180 @@
+use fei_common::prelude::*;
+use crate::{
+    entity::Entity,
+    observer::{
+        ObserverEvent, ObserverKey, Trigger,
+    },
+    system::BoxedSystem,
+    world::{
+        World, WorldCell,
+    },
+};
+use std::{
+    any::Any,
+    hash::Hash,
+    sync::Arc,
+};
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct ObserverId(pub(crate) usize);
+
+/// Observer systems of the [`World`], grouped by the [key](ObserverKey) of the events they observe.
+#[derive(Default)]
+pub struct Observers {
+    entries: Vec<Option<ObserverEntry>>,
+    keys: FxHashMap<ObserverKey, Vec<ObserverId>>,
+    targeted: FxHashMap<Entity, Vec<ObserverId>>,
+}
+
+struct ObserverEntry {
+    key: ObserverKey,
+    targets: Option<Vec<Entity>>,
+    /// `None` while the observer is [taken](Observers::take) out to run.
+    slot: Option<ObserverSlot>,
+}
+
+pub(crate) struct ObserverSlot {
+    runner: Box<dyn ObserverRunner>,
+}
+
+pub(crate) trait ObserverRunner: 'static + Send + Sync {
+    /// Runs the observer without applying its deferred state. Lifecycle events are passed as `None`.
+    unsafe fn run(&mut self, world: WorldCell, event: Option<Arc<dyn Any + Send + Sync>>, entity: Option<Entity>) -> anyhow::Result<()>;
+
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()>;
+}
+
+impl<E: ObserverEvent> ObserverRunner for BoxedSystem<Trigger<E>> {
+    #[inline]
+    unsafe fn run(&mut self, world: WorldCell, event: Option<Arc<dyn Any + Send + Sync>>, entity: Option<Entity>) -> anyhow::Result<()> {
+        let event = match event {
+            Some(event) => event.downcast::<E>().ok(),
+            None => E::lifecycle().map(Arc::new),
+        };
+
+        match event {
+            Some(event) => self.call_unchecked(Trigger::new(event, entity), world),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        (**self).apply(world)
+    }
+}
+
+impl Observers {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.values().map(Vec::len).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether any observer observes events of the given key.
+    #[inline]
+    pub fn contains(&self, key: ObserverKey) -> bool {
+        self.keys.contains_key(&key)
+    }
+
+    #[inline]
+    pub(crate) fn insert<E: ObserverEvent>(&mut self, key: ObserverKey, targets: Option<Vec<Entity>>, system: BoxedSystem<Trigger<E>>) -> ObserverId {
+        let id = ObserverId(self.entries.len());
+        for &target in targets.iter().flatten() {
+            self.targeted.entry(target).or_default().push(id);
+        }
+
+        self.entries.push(Some(ObserverEntry {
+            key,
+            targets,
+            slot: Some(ObserverSlot { runner: Box::new(system), }),
+        }));
+
+        self.keys.entry(key).or_default().push(id);
+        id
+    }
+
+    /// Removes an observer, returning whether it existed.
+    #[inline]
+    pub fn remove(&mut self, id: ObserverId) -> bool {
+        let Some(entry) = self.entries.get_mut(id.0).and_then(Option::take) else { return false };
+        Self::unlist(&mut self.keys, entry.key, id);
+        for &target in entry.targets.iter().flatten() {
+            Self::unlist(&mut self.targeted, target, id);
+        }
+
+        true
+    }
+
+    /// Returns whether any observer targets `entity`.
+    #[inline]
+    pub(crate) fn is_targeting(&self, entity: Entity) -> bool {
+        self.targeted.contains_key(&entity)
+    }
+
+    /// Stops observers from targeting a despawned entity, removing the ones that targeted only it.
+    pub(crate) fn remove_target(&mut self, entity: Entity) {
+        let Some(ids) = self.targeted.remove(&entity) else { return };
+        for id in ids {
+            let Some(targets) = self.entries[id.0].as_mut().and_then(|entry| entry.targets.as_mut()) else { continue };
+            targets.retain(|&target| target != entity);
+            if targets.is_empty() {
+                self.remove(id);
+            }
+        }
+    }
+
+    /// Returns the observers of the given key that `entity` triggers: global ones, and ones that
+    /// target the entity.
+    #[inline]
+    pub(crate) fn matching(&self, key: ObserverKey, entity: Option<Entity>) -> Vec<ObserverId> {
+        self.keys.get(&key).map_or_else(Vec::new, |ids| ids
+            .iter().copied()
+            .filter(|id| self.entries[id.0].as_ref().is_some_and(|entry| match &entry.targets {
+                None => true,
+                Some(targets) => entity.is_some_and(|entity| targets.contains(&entity)),
+            }))
+            .collect()
+        )
+    }
+
+    /// Takes an observer out to run it. Observers being run can't be triggered recursively.
+    #[inline]
+    pub(crate) fn take(&mut self, id: ObserverId) -> Option<ObserverSlot> {
+        self.entries.get_mut(id.0)?.as_mut()?.slot.take()
+    }
+
+    /// Puts back an observer [taken](Observers::take) out, unless it has been removed meanwhile.
+    #[inline]
+    pub(crate) fn restore(&mut self, id: ObserverId, slot: ObserverSlot) {
+        if let Some(entry) = &mut self.entries[id.0] {
+            entry.slot = Some(slot);
+        }
+    }
+
+    #[inline]
+    fn unlist<K: Eq + Hash>(lists: &mut FxHashMap<K, Vec<ObserverId>>, key: K, id: ObserverId) {
+        if let Some(ids) = lists.get_mut(&key) {
+            ids.retain(|&other| other != id);
+            if ids.is_empty() {
+                lists.remove(&key);
+            }
+        }
+    }
+}
+
+impl ObserverSlot {
+    #[inline]
+    pub(crate) unsafe fn run(&mut self, world: WorldCell, event: Option<Arc<dyn Any + Send + Sync>>, entity: Option<Entity>) -> anyhow::Result<()> {
+        self.runner.run(world, event, entity)
+    }
+
+    #[inline]
+    pub(crate) fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        self.runner.apply(world)
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    component::{
+        Component, ComponentId,
+    },
+    entity::Entity,
+    event::Event,
+    world::World,
+};
+use std::{
+    any::TypeId,
+    marker::PhantomData,
+    ops::Deref,
+    sync::Arc,
+};
+
+/// Identifies which observers an event triggers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ObserverKey {
+    /// A custom [`Event`], triggered with [`World::trigger`].
+    Event(TypeId),
+    /// [`OnAdd`] of the component.
+    Add(ComponentId),
+    /// [`OnRemove`] of the component.
+    Remove(ComponentId),
+}
+
+/// Events observers may react to: custom [`Event`]s and component lifecycle events, i.e. [`OnAdd`]
+/// and [`OnRemove`].
+pub trait ObserverEvent: 'static + Send + Sync + Sized {
+    /// Returns the key observers of this event are registered with.
+    fn key(world: &mut World) -> ObserverKey;
+
+    /// Creates the event if it's a component lifecycle event, which the world triggers by itself.
+    #[inline]
+    fn lifecycle() -> Option<Self> {
+        None
+    }
+}
+
+impl<E: Event> ObserverEvent for E {
+    #[inline]
+    fn key(_: &mut World) -> ObserverKey {
+        ObserverKey::Event(TypeId::of::<E>())
+    }
+}
+
+/// Triggered after a component of type `T` is inserted into an entity that didn't have it, after
+/// its [`on_add`](crate::component::ComponentHooks::on_add) hook.
+pub struct OnAdd<T: Component>(PhantomData<fn() -> T>);
+impl<T: Component> ObserverEvent for OnAdd<T> {
+    #[inline]
+    fn key(world: &mut World) -> ObserverKey {
+        ObserverKey::Add(world.register_component::<T>())
+    }
+
+    #[inline]
+    fn lifecycle() -> Option<Self> {
+        Some(Self(PhantomData))
+    }
+}
+
+/// Triggered before a component of type `T` is removed from an entity, after its
+/// [`on_remove`](crate::component::ComponentHooks::on_remove) hook.
+pub struct OnRemove<T: Component>(PhantomData<fn() -> T>);
+impl<T: Component> ObserverEvent for OnRemove<T> {
+    #[inline]
+    fn key(world: &mut World) -> ObserverKey {
+        ObserverKey::Remove(world.register_component::<T>())
+    }
+
+    #[inline]
+    fn lifecycle() -> Option<Self> {
+        Some(Self(PhantomData))
+    }
+}
+
+/// The input of observer systems, holding the event and the entity it targets, if any.
+pub struct Trigger<E: ObserverEvent> {
+    event: Arc<E>,
+    entity: Option<Entity>,
+}
+
+impl<E: ObserverEvent> Trigger<E> {
+    #[inline]
+    pub(crate) fn new(event: Arc<E>, entity: Option<Entity>) -> Self {
+        Self { event, entity, }
+    }
+
+    #[inline]
+    pub fn event(&self) -> &E {
+        &self.event
+    }
+
+    /// Returns the entity the event targets, or `None` if it was triggered globally.
+    #[inline]
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+}
+
+impl<E: ObserverEvent> Deref for Trigger<E> {
+    type Target = E;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.event
+    }
+}
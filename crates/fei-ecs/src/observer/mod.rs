@@ -0,0 +1,153 @@
+mod collection;
+mod def;
+
+pub use collection::*;
+pub use def::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_common::prelude::*;
+    use fei_ecs_macros::{
+        Component, Event, Resource,
+    };
+    use crate::{
+        command::{
+            CommandQueue, Commands,
+        },
+        entity::Entity,
+        query::Query,
+        resource::ResMut,
+        system::In,
+        world::World,
+    };
+
+    #[derive(Component)]
+    struct Health(u32);
+    #[derive(Event)]
+    struct Damage(u32);
+    #[derive(Resource, Default)]
+    struct Log(Vec<(Option<Entity>, u32)>);
+
+    #[inline]
+    fn take_log(world: &mut World) -> Vec<(Option<Entity>, u32)> {
+        std::mem::take(&mut world.res_mut::<Log>().unwrap().0)
+    }
+
+    #[test]
+    fn events() -> anyhow::Result<()> {
+        fn global(trigger: In<Trigger<Damage>>, mut log: ResMut<Log>) -> anyhow::Result<()> {
+            log.0.push((trigger.0.entity(), trigger.0.0));
+            Ok(())
+        }
+
+        fn damage(trigger: In<Trigger<Damage>>, mut query: Query<&mut Health>, mut commands: Commands) -> anyhow::Result<()> {
+            let entity = trigger.0.entity().unwrap();
+            let mut health = query.get_mut(entity)?;
+            health.0 = health.0.saturating_sub(trigger.0.0);
+            if health.0 == 0 {
+                commands.entity(entity).despawn();
+            }
+
+            Ok(())
+        }
+
+        let mut world = World::default();
+        world.init_res::<Log>();
+
+        let a = world.spawn(Health(5))?.id();
+        let b = world.spawn(Health(5))?.id();
+        let global = world.observe(global)?;
+        world.view_mut(a)?.observe(damage)?;
+        world.view_mut(b)?.observe(damage)?;
+        assert_eq!(world.observers().len(), 3);
+
+        // Global triggers only run global observers.
+        world.trigger(Damage(1))?;
+        assert_eq!(take_log(&mut world), [(None, 1)]);
+        assert_eq!(world.view(a)?.get::<Health>().map(|health| health.0), Some(5));
+
+        world.trigger_targets(Damage(2), [a, b])?;
+        assert_eq!(take_log(&mut world), [(Some(a), 2), (Some(b), 2)]);
+        assert_eq!(world.view(a)?.get::<Health>().map(|health| health.0), Some(3));
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(world.entities(), &mut queue);
+        commands.entity(a).trigger(Damage(3));
+        commands.trigger(Damage(4));
+        queue.apply(&mut world)?;
+
+        assert!(world.view(a).is_err());
+        assert_eq!(world.view(b)?.get::<Health>().map(|health| health.0), Some(3));
+        assert_eq!(take_log(&mut world), [(Some(a), 3), (None, 4)]);
+
+        assert!(world.remove_observer(global));
+        assert!(!world.remove_observer(global));
+        world.trigger(Damage(1))?;
+        assert!(take_log(&mut world).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn lifecycle() -> anyhow::Result<()> {
+        fn added(trigger: In<Trigger<OnAdd<Health>>>, query: Query<&Health>, mut log: ResMut<Log>) -> anyhow::Result<()> {
+            let entity = trigger.0.entity().unwrap();
+            log.0.push((Some(entity), query.get(entity)?.0));
+            Ok(())
+        }
+
+        // Removed components are still readable.
+        fn removed(trigger: In<Trigger<OnRemove<Health>>>, query: Query<&Health>, mut commands: Commands) -> anyhow::Result<()> {
+            let health = query.get(trigger.0.entity().unwrap())?.0;
+            commands.spawn(Health(health + 10))?;
+            Ok(())
+        }
+
+        let mut world = World::default();
+        world.init_res::<Log>();
+        world.observe(added)?;
+
+        let a = world.spawn(Health(1))?.id();
+        world.view_mut(a)?.insert(Health(2));
+        assert_eq!(take_log(&mut world), [(Some(a), 1)]);
+
+        let b = world.spawn(Health(3))?.id();
+        world.view_mut(b)?.observe(removed)?;
+        world.despawn(a)?;
+        world.despawn(b)?;
+
+        // Commands of lifecycle observers are applied at the end of the structural change.
+        assert_eq!(world.query::<&Health>().iter(&world).map(|health| health.0).collect::<Vec<_>>(), [13]);
+        assert_eq!(take_log(&mut world).into_iter().map(|(.., health)| health).collect::<Vec<_>>(), [3, 13]);
+        Ok(())
+    }
+
+    #[test]
+    fn despawned_targets() -> anyhow::Result<()> {
+        fn targeted(trigger: In<Trigger<Damage>>, mut log: ResMut<Log>) -> anyhow::Result<()> {
+            log.0.push((trigger.0.entity(), trigger.0.0));
+            Ok(())
+        }
+
+        let mut world = World::default();
+        world.init_res::<Log>();
+
+        let a = world.spawn(Health(1))?.id();
+        let b = world.spawn(Health(1))?.id();
+        world.view_mut(a)?.observe(targeted)?;
+        let both = world.observe_targets([a, b], targeted)?;
+        assert_eq!(world.observers().len(), 2);
+
+        // Observers are removed once all of their targets are despawned.
+        world.despawn(a)?;
+        assert_eq!(world.observers().len(), 1);
+
+        world.trigger_targets(Damage(1), [b])?;
+        assert_eq!(take_log(&mut world), [(Some(b), 1)]);
+
+        world.despawn(b)?;
+        assert!(world.observers().is_empty());
+        assert!(!world.remove_observer(both));
+        Ok(())
+    }
+}
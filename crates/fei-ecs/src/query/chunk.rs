@@ -0,0 +1,208 @@
+use fei_common::prelude::*;
+use crate::{
+    component::{
+        Component, ComponentStorage,
+        Components, Table, TableId,
+    },
+    entity::{
+        Entity,
+        Entities,
+    },
+    query::{
+        QueryData, ArchetypeFilter,
+    },
+    world::WorldCell,
+    ChangeMark,
+};
+use fixedbitset::FixedBitSet;
+use std::{
+    marker::PhantomData,
+    ops::Range,
+    slice::{
+        self,
+        Iter,
+    },
+};
+
+/// [`QueryData`] that may be fetched as contiguous slices of table columns instead of one entity at a
+/// time, e.g. `(Entity, &A, &mut B)` yielding `(&[Entity], &[A], &mut [B])`. See [`QueryChunkIter`].
+///
+/// # Safety
+/// [`fetch_chunk`](ChunkQueryData::fetch_chunk) must not access components other than the ones
+/// registered in [`init_state`](crate::query::WorldQuery::init_state).
+pub unsafe trait ChunkQueryData: QueryData {
+    /// The slices produced for each chunk.
+    type Chunk<'w>;
+
+    /// Returns whether every accessed component is stored in tables, which is required for chunk
+    /// iteration. Sparse-set and zero-sized components aren't.
+    fn is_dense(state: &Self::State) -> bool;
+
+    /// Fetches the given rows of the table.
+    ///
+    /// # Safety
+    /// - The query must be [dense](ChunkQueryData::is_dense), and the table must belong to an
+    ///   archetype matching it.
+    /// - `rows` must be in bounds of the table.
+    /// - There may not be other conflicting accesses to the rows.
+    unsafe fn fetch_chunk<'w>(state: &Self::State, table: &'w Table, rows: Range<usize>, current: ChangeMark) -> Self::Chunk<'w>;
+}
+
+unsafe impl ChunkQueryData for Entity {
+    type Chunk<'w> = &'w [Entity];
+
+    #[inline]
+    fn is_dense(_: &Self::State) -> bool {
+        true
+    }
+
+    #[inline]
+    unsafe fn fetch_chunk<'w>(_: &Self::State, table: &'w Table, rows: Range<usize>, _: ChangeMark) -> Self::Chunk<'w> {
+        table.entities().get_unchecked(rows)
+    }
+}
+
+unsafe impl<T: Component> ChunkQueryData for &T {
+    type Chunk<'w> = &'w [T];
+
+    #[inline]
+    fn is_dense(state: &Self::State) -> bool {
+        state.storage() == Some(ComponentStorage::Table)
+    }
+
+    #[inline]
+    unsafe fn fetch_chunk<'w>(state: &Self::State, table: &'w Table, rows: Range<usize>, _: ChangeMark) -> Self::Chunk<'w> {
+        table.column(state.id()).as_slice::<T>().get_unchecked(rows)
+    }
+}
+
+/// Marks every fetched component as updated, whether or not it's actually written to.
+unsafe impl<T: Component> ChunkQueryData for &mut T {
+    type Chunk<'w> = &'w mut [T];
+
+    #[inline]
+    fn is_dense(state: &Self::State) -> bool {
+        state.storage() == Some(ComponentStorage::Table)
+    }
+
+    #[inline]
+    unsafe fn fetch_chunk<'w>(state: &Self::State, table: &'w Table, rows: Range<usize>, current: ChangeMark) -> Self::Chunk<'w> {
+        for ticks in table.ticks(state.id()).get_unchecked(rows.clone()) {
+            *ticks.updated.get() = current;
+        }
+
+        let column = table.column(state.id());
+        slice::from_raw_parts_mut(column.as_ptr().cast::<T>().cast_mut().add(rows.start), rows.len())
+    }
+}
+
+macro_rules! impl_chunk_query_data {
+    ($($tuple_type:ident $tuple_index:tt),*) => {
+        unsafe impl<$($tuple_type: ChunkQueryData,)*> ChunkQueryData for ($($tuple_type,)*) {
+            type Chunk<'w> = ($($tuple_type::Chunk<'w>,)*);
+
+            #[inline]
+            #[allow(unused)]
+            fn is_dense(state: &Self::State) -> bool {
+                true $(&& $tuple_type::is_dense(&state.$tuple_index))*
+            }
+
+            #[inline]
+            #[allow(unused, clippy::unused_unit)]
+            unsafe fn fetch_chunk<'w>(state: &Self::State, table: &'w Table, rows: Range<usize>, current: ChangeMark) -> Self::Chunk<'w> {
+                ($($tuple_type::fetch_chunk(&state.$tuple_index, table, rows.clone(), current),)*)
+            }
+        }
+    }
+} impl_tuples!(impl_chunk_query_data! 8);
+
+/// Iterates entities matching a query as contiguous slices of table columns, so loops over them may
+/// be auto-vectorized. Each chunk spans the whole table, unless some of its rows belong to
+/// archetypes not matching the query; those are skipped by splitting the table into multiple chunks.
+pub struct QueryChunkIter<'w, 's, Q: ChunkQueryData, F: ArchetypeFilter = ()> {
+    components: &'w Components,
+    entities: &'w Entities,
+
+    state: &'s Q::State,
+    tables: Iter<'s, TableId>,
+    archetype_bits: &'s FixedBitSet,
+    partial_tables: &'s FixedBitSet,
+    current: ChangeMark,
+
+    table: Option<(&'w Table, bool)>,
+    row: usize,
+    _marker: PhantomData<fn() -> F>,
+}
+
+impl<'w, 's, Q: ChunkQueryData, F: ArchetypeFilter> QueryChunkIter<'w, 's, Q, F> {
+    /// # Safety
+    /// - `Q` must be [dense](ChunkQueryData::is_dense).
+    /// - `tables` must only contain IDs of tables in `world` holding archetypes matching both `Q`
+    ///   and `F`, which are exactly the ones in `archetype_bits`. `partial_tables` must contain the
+    ///   tables also holding archetypes not matching them.
+    /// - There may not be other conflicting accesses to the components `Q` and `F` access.
+    #[inline]
+    pub(crate) unsafe fn new(
+        world: WorldCell<'w>, state: &'s Q::State,
+        tables: &'s [TableId], archetype_bits: &'s FixedBitSet, partial_tables: &'s FixedBitSet,
+        current: ChangeMark,
+    ) -> Self {
+        Self {
+            components: world.components(),
+            entities: world.entities(),
+
+            state,
+            tables: tables.iter(),
+            archetype_bits,
+            partial_tables,
+            current,
+
+            table: None,
+            row: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn matches(&self, entity: Entity) -> bool {
+        // Safety: Entities residing in tables always have a location.
+        let location = unsafe { self.entities.location(entity).unwrap_unchecked() };
+        self.archetype_bits.contains(location.archetype_id.0)
+    }
+}
+
+impl<'w, 's, Q: ChunkQueryData, F: ArchetypeFilter> Iterator for QueryChunkIter<'w, 's, Q, F> {
+    type Item = Q::Chunk<'w>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((table, partial)) = self.table {
+                let entities = table.entities();
+                if partial {
+                    while self.row < entities.len() && !self.matches(entities[self.row]) {
+                        self.row += 1;
+                    }
+                }
+
+                if self.row < entities.len() {
+                    let start = self.row;
+                    self.row = if partial {
+                        (start..entities.len()).find(|&row| !self.matches(entities[row])).unwrap_or(entities.len())
+                    } else {
+                        entities.len()
+                    };
+
+                    // Safety: The rows are in bounds, and only belong to matching archetypes.
+                    return Some(unsafe { Q::fetch_chunk(self.state, table, start..self.row, self.current) })
+                }
+            }
+
+            let &id = self.tables.next()?;
+
+            // Safety: Table IDs are always valid.
+            self.table = Some((unsafe { self.components.tables().get_unchecked(id.0) }, self.partial_tables.contains(id.0)));
+            self.row = 0;
+        }
+    }
+}
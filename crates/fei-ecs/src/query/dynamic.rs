@@ -0,0 +1,323 @@
+use fei_common::{
+    prelude::*,
+    ptr::{
+        Ptr, PtrMut,
+    },
+};
+use crate::{
+    component::{
+        ComponentId,
+        Archetype, Table,
+    },
+    entity::Entity,
+    query::{
+        WorldQuery, QueryData, ReadOnlyQueryData,
+        QueryState,
+        ComponentState, ComponentFetch,
+    },
+    system::{
+        Access, AccessConflict,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeMark, ChangeAware, ChangeAwareMut,
+    MutErased,
+};
+use fixedbitset::FixedBitSet;
+
+#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum QueryBuildError {
+    #[error("component `{:?}` isn't registered", .0)]
+    Unregistered(ComponentId),
+    #[error("query conflicts with itself in accessing {:?}", .0)]
+    Conflict(AccessConflict),
+}
+
+/// Assembles a query from [`ComponentId`]s known only at runtime, e.g. of components registered
+/// from a [`ComponentDescriptor`](crate::component::ComponentDescriptor).
+///
+/// Read and write terms, including optional ones, are fetched by [`DynamicQuery`] in the order
+/// they're added, while [`with`](QueryBuilder::with) and [`without`](QueryBuilder::without) only
+/// filter archetypes.
+pub struct QueryBuilder<'w> {
+    world: &'w World,
+    terms: Vec<(ComponentId, bool, bool)>,
+    with: Vec<ComponentId>,
+    without: Vec<ComponentId>,
+}
+
+impl<'w> QueryBuilder<'w> {
+    #[inline]
+    pub fn new(world: &'w World) -> Self {
+        Self {
+            world,
+            terms: Vec::new(),
+            with: Vec::new(),
+            without: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn read(&mut self, id: ComponentId) -> &mut Self {
+        self.terms.push((id, false, false));
+        self
+    }
+
+    #[inline]
+    pub fn write(&mut self, id: ComponentId) -> &mut Self {
+        self.terms.push((id, true, false));
+        self
+    }
+
+    /// Reads the component if the entity holds it, without requiring it to.
+    #[inline]
+    pub fn optional_read(&mut self, id: ComponentId) -> &mut Self {
+        self.terms.push((id, false, true));
+        self
+    }
+
+    /// Writes the component if the entity holds it, without requiring it to.
+    #[inline]
+    pub fn optional_write(&mut self, id: ComponentId) -> &mut Self {
+        self.terms.push((id, true, true));
+        self
+    }
+
+    #[inline]
+    pub fn with(&mut self, id: ComponentId) -> &mut Self {
+        self.with.push(id);
+        self
+    }
+
+    #[inline]
+    pub fn without(&mut self, id: ComponentId) -> &mut Self {
+        self.without.push(id);
+        self
+    }
+
+    /// Creates the query state, failing if any component isn't registered or the terms conflict with
+    /// each other, e.g. in reading and writing the same component.
+    pub fn build(&mut self) -> Result<QueryState<DynamicQuery>, QueryBuildError> {
+        let world = self.world;
+        let registered = |id| world.components().get_info(id).map(|info| (id, info.layout().align())).ok_or(QueryBuildError::Unregistered(id));
+
+        let mut state = DynamicState::default();
+        for &(id, write, optional) in &self.terms {
+            let (id, align) = registered(id)?;
+            state.terms.push(DynamicTerm {
+                // Safety: The component is registered.
+                state: unsafe { ComponentState::from_id(world, id).unwrap_unchecked() },
+                align,
+                write,
+            });
+
+            if !optional {
+                state.required.grow(id.0 + 1);
+                state.required.insert(id.0);
+            }
+        }
+
+        DynamicQuery::update_access(&state, &mut Access::default(), true).map_err(QueryBuildError::Conflict)?;
+
+        let mut query = QueryState::from_state(world, state, ());
+        for &id in &self.with {
+            query = query.with_id(registered(id)?.0);
+        }
+
+        for &id in &self.without {
+            query = query.without_id(registered(id)?.0);
+        }
+
+        query.update_archetypes(world);
+        Ok(query)
+    }
+}
+
+struct DynamicTerm {
+    state: ComponentState,
+    align: usize,
+    write: bool,
+}
+
+/// Persistent state of [`DynamicQuery`], assembled by a [`QueryBuilder`].
+#[derive(Default)]
+pub struct DynamicState {
+    terms: Vec<DynamicTerm>,
+    required: FixedBitSet,
+}
+
+/// Cached data of [`DynamicQuery`].
+pub struct DynamicFetch<'w> {
+    terms: Vec<DynamicTermFetch<'w>>,
+}
+
+struct DynamicTermFetch<'w> {
+    id: ComponentId,
+    fetch: ComponentFetch<'w>,
+    align: usize,
+    write: bool,
+    matches: bool,
+}
+
+enum DynamicPtr<'w> {
+    Ref(Ptr<'w>),
+    Mut(MutErased<'w>),
+}
+
+/// An entity fetched by a [`DynamicQuery`], along with its components in the order of the query's
+/// terms.
+pub struct DynamicItem<'w> {
+    entity: Entity,
+    components: Vec<Option<DynamicPtr<'w>>>,
+}
+
+impl<'w> DynamicItem<'w> {
+    #[inline]
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Returns the number of terms, including optional ones the entity doesn't match.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Returns the component of the term at `index`, or `None` if the entity doesn't hold it.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<Ptr<'_>> {
+        match self.components.get(index)?.as_ref()? {
+            DynamicPtr::Ref(ptr) => Some(*ptr),
+            DynamicPtr::Mut(ptr) => Some(ptr.get()),
+        }
+    }
+
+    /// Returns the component of the write term at `index` mutably, marking it as updated. Returns
+    /// `None` if the entity doesn't hold it, or the term is a read term.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<PtrMut<'_>> {
+        match self.components.get_mut(index)?.as_mut()? {
+            DynamicPtr::Ref(..) => None,
+            DynamicPtr::Mut(ptr) => Some(ptr.get_mut()),
+        }
+    }
+}
+
+/// A query fetching components by their [`ComponentId`]s, built with [`QueryBuilder`]. Unlike other
+/// [`WorldQuery`]s, its state can't be initialized from the world alone; an
+/// [initialized](WorldQuery::init_state) one fetches nothing.
+pub struct DynamicQuery;
+
+/// The [read-only](QueryData::ReadOnly) counterpart of [`DynamicQuery`], fetching write terms
+/// immutably.
+pub struct DynamicQueryReadOnly;
+
+impl DynamicQuery {
+    #[inline]
+    fn update_access(state: &DynamicState, access: &mut Access, write: bool) -> Result<(), AccessConflict> {
+        for term in &state.terms {
+            let mut element = Access::default();
+            if write && term.write {
+                element.add_component_write(term.state.id());
+            } else {
+                element.add_component_read(term.state.id());
+            }
+
+            if let Some(conflict) = access.get_conflict(&element) {
+                return Err(conflict)
+            }
+
+            access.extend(&element);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: &mut DynamicFetch<'w>, entity: Entity, table_index: usize, write: bool) -> DynamicItem<'w> {
+        DynamicItem {
+            entity,
+            components: fetch.terms.iter().map(|term| term.matches.then(|| {
+                let ptr = term.fetch.get_aligned(entity, table_index, term.align);
+                if write && term.write {
+                    let ticks = term.fetch.ticks(entity, table_index);
+                    DynamicPtr::Mut(MutErased::new(ptr.unique(), &ticks.added, &ticks.updated, term.fetch.last, term.fetch.current))
+                } else {
+                    DynamicPtr::Ref(ptr)
+                }
+            })).collect(),
+        }
+    }
+}
+
+macro_rules! impl_dynamic_query {
+    ($target:ident, $write:literal) => {
+        unsafe impl WorldQuery for $target {
+            type Item<'w> = DynamicItem<'w>;
+            type Fetch<'w> = DynamicFetch<'w>;
+            type State = DynamicState;
+
+            #[inline]
+            fn init_state(_: &mut World) -> Self::State {
+                default()
+            }
+
+            #[inline]
+            fn matches(state: &Self::State, component_bits: &FixedBitSet) -> bool {
+                state.required.is_subset(component_bits)
+            }
+
+            #[inline]
+            fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict> {
+                DynamicQuery::update_access(state, access, $write)
+            }
+
+            #[inline]
+            unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w> {
+                DynamicFetch {
+                    terms: state.terms.iter().map(|term| DynamicTermFetch {
+                        id: term.state.id(),
+                        fetch: ComponentFetch::new(world, &term.state, last, current),
+                        align: term.align,
+                        write: term.write,
+                        matches: false,
+                    }).collect(),
+                }
+            }
+
+            #[inline]
+            unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, _: &Self::State, archetype: &'w Archetype, table: Option<&'w Table>) {
+                for term in &mut fetch.terms {
+                    term.matches = archetype.component_bits().contains(term.id.0);
+                    if term.matches {
+                        term.fetch.set_table(table);
+                    }
+                }
+            }
+
+            #[inline]
+            unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: Entity, table_index: usize) -> Self::Item<'w> {
+                DynamicQuery::fetch(fetch, entity, table_index, $write)
+            }
+        }
+    };
+}
+
+impl_dynamic_query!(DynamicQuery, true);
+impl_dynamic_query!(DynamicQueryReadOnly, false);
+
+unsafe impl QueryData for DynamicQuery {
+    type ReadOnly = DynamicQueryReadOnly;
+}
+
+unsafe impl QueryData for DynamicQueryReadOnly {
+    type ReadOnly = Self;
+}
+
+unsafe impl ReadOnlyQueryData for DynamicQueryReadOnly {}
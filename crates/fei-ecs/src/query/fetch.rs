@@ -0,0 +1,486 @@
+use fei_common::{
+    prelude::*,
+    ptr::Ptr,
+};
+use crate::{
+    component::{
+        Component, ComponentId, ComponentStorage, ComponentTicks,
+        Archetype, Table,
+    },
+    entity::Entity,
+    system::{
+        Access, AccessConflict,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeMark,
+    Ref, RefErased,
+    Mut, MutErased,
+};
+use fixedbitset::FixedBitSet;
+use std::{
+    mem,
+    ptr::NonNull,
+};
+
+/// Types that fetch data from [`World`]s archetype by archetype, used by queries to iterate
+/// entities.
+///
+/// # Safety
+/// - [`matches`](WorldQuery::matches) must only return `true` if the archetype contains every
+///   component the query non-optionally accesses.
+/// - [`fetch`](WorldQuery::fetch) must not access components other than the ones registered in
+///   [`init_state`](WorldQuery::init_state).
+pub unsafe trait WorldQuery {
+    /// The item produced for each entity.
+    type Item<'w>;
+    /// Cached data used while iterating, e.g. columns of the current table.
+    type Fetch<'w>;
+    /// Persistent data, e.g. the [`ComponentId`]s the query accesses.
+    type State: 'static + Send + Sync;
+
+    /// Registers the accessed components and creates the persistent state.
+    fn init_state(world: &mut World) -> Self::State;
+
+    /// Returns whether an archetype holding the given components may be iterated.
+    fn matches(state: &Self::State, component_bits: &FixedBitSet) -> bool;
+
+    /// Registers the components the query reads and writes, returning the first conflict between
+    /// its own accesses, e.g. in `(&mut T, &T)`.
+    fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict>;
+
+    /// Creates the cached data used while iterating.
+    ///
+    /// # Safety
+    /// - `state` must be created from the same world `world` refers to.
+    /// - There may not be other conflicting accesses to the components the query accesses.
+    unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w>;
+
+    /// Prepares the cached data to fetch entities residing in the given archetype.
+    ///
+    /// # Safety
+    /// - `archetype` must [match](WorldQuery::matches) the query.
+    /// - `table` must be the table `archetype` refers to.
+    unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, state: &Self::State, archetype: &'w Archetype, table: Option<&'w Table>);
+
+    /// Fetches the item of an entity residing in the last [set](WorldQuery::set_archetype)
+    /// archetype. `table_index` is meaningless if the archetype doesn't refer to a table.
+    ///
+    /// # Safety
+    /// - `entity` must reside in the last set archetype, at `table_index` if it refers to a table.
+    /// - Mutable items of the same entity must not alias.
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: Entity, table_index: usize) -> Self::Item<'w>;
+}
+
+/// [`WorldQuery`]s that may be used as the data of a [`Query`](crate::query::Query).
+///
+/// # Safety
+/// [`ReadOnly`](QueryData::ReadOnly) must access the same components, but never mutably.
+pub unsafe trait QueryData: WorldQuery {
+    type ReadOnly: ReadOnlyQueryData<State = Self::State>;
+}
+
+/// [`QueryData`] that only reads components.
+///
+/// # Safety
+/// The query must never access any component mutably.
+pub unsafe trait ReadOnlyQueryData: QueryData<ReadOnly = Self> {}
+
+/// Persistent state of queries accessing a single component type.
+pub struct ComponentState {
+    id: ComponentId,
+    storage: Option<ComponentStorage>,
+}
+
+impl ComponentState {
+    #[inline]
+    pub fn new<T: Component>(world: &mut World) -> Self {
+        let id = world.register_component::<T>();
+        Self {
+            id,
+            // Safety: The component has just been registered.
+            storage: unsafe { world.components().get_info(id).unwrap_unchecked() }.storage(),
+        }
+    }
+
+    /// Creates the state of an already-registered component, e.g. one registered from a
+    /// [`ComponentDescriptor`](crate::component::ComponentDescriptor).
+    #[inline]
+    pub fn from_id(world: &World, id: ComponentId) -> Option<Self> {
+        let info = world.components().get_info(id)?;
+        Some(Self { id, storage: info.storage(), })
+    }
+
+    #[inline]
+    pub fn id(&self) -> ComponentId {
+        self.id
+    }
+
+    #[inline]
+    pub fn storage(&self) -> Option<ComponentStorage> {
+        self.storage
+    }
+}
+
+/// Cached data of queries accessing a single component type.
+pub struct ComponentFetch<'w> {
+    id: ComponentId,
+    storage: Option<ComponentStorage>,
+    column: Option<&'w VecErased<'static>>,
+    column_ticks: Option<&'w [ComponentTicks]>,
+    sparse_set: Option<&'w SparseSetErased<'static, u32>>,
+    sparse_ticks: Option<&'w SparseSet<u32, ComponentTicks>>,
+    pub(crate) last: ChangeMark,
+    pub(crate) current: ChangeMark,
+}
+
+impl<'w> ComponentFetch<'w> {
+    #[inline]
+    pub(crate) unsafe fn new(world: WorldCell<'w>, state: &ComponentState, last: ChangeMark, current: ChangeMark) -> Self {
+        let components = world.components();
+        let (sparse_set, sparse_ticks) = match state.storage {
+            Some(ComponentStorage::Table) => (None, None),
+            Some(ComponentStorage::SparseSet) => (
+                Some(components.sparse_sets().set(state.id)),
+                Some(components.sparse_sets().ticks(state.id)),
+            ),
+            None => (None, Some(components.bitsets().ticks(state.id))),
+        };
+
+        Self {
+            id: state.id,
+            storage: state.storage,
+            column: None,
+            column_ticks: None,
+            sparse_set,
+            sparse_ticks,
+            last,
+            current,
+        }
+    }
+
+    #[inline]
+    pub(crate) unsafe fn set_table(&mut self, table: Option<&'w Table>) {
+        if let Some(ComponentStorage::Table) = self.storage {
+            let table = table.unwrap_unchecked();
+            self.column = Some(table.column(self.id));
+            self.column_ticks = Some(table.ticks(self.id));
+        }
+    }
+
+    #[inline]
+    pub(crate) unsafe fn get<T: Component>(&self, entity: Entity, table_index: usize) -> Ptr<'w> {
+        self.get_aligned(entity, table_index, mem::align_of::<T>())
+    }
+
+    /// Like [`get`](ComponentFetch::get), but zero-sized components are pointed to with the given
+    /// alignment instead of the type's.
+    #[inline]
+    pub(crate) unsafe fn get_aligned(&self, entity: Entity, table_index: usize, align: usize) -> Ptr<'w> {
+        match self.storage {
+            Some(ComponentStorage::Table) => self.column.unwrap_unchecked().get_unchecked(table_index),
+            Some(ComponentStorage::SparseSet) => self.sparse_set.unwrap_unchecked().get_unchecked(entity.id()),
+            // Safety: Alignments are never zero, and zero-sized reads through dangling pointers with
+            // the right alignment are valid.
+            None => Ptr::new(NonNull::new_unchecked(align as *mut u8)),
+        }
+    }
+
+    #[inline]
+    pub(crate) unsafe fn ticks(&self, entity: Entity, table_index: usize) -> &'w ComponentTicks {
+        match self.storage {
+            Some(ComponentStorage::Table) => self.column_ticks.unwrap_unchecked().get_unchecked(table_index),
+            _ => self.sparse_ticks.unwrap_unchecked().get_unchecked(entity.id()),
+        }
+    }
+}
+
+unsafe impl WorldQuery for Entity {
+    type Item<'w> = Entity;
+    type Fetch<'w> = ();
+    type State = ();
+
+    #[inline]
+    fn init_state(_: &mut World) -> Self::State {}
+
+    #[inline]
+    fn matches(_: &Self::State, _: &FixedBitSet) -> bool {
+        true
+    }
+
+    #[inline]
+    fn update_access(_: &Self::State, _: &mut Access) -> Result<(), AccessConflict> {
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn init_fetch<'w>(_: WorldCell<'w>, _: &Self::State, _: ChangeMark, _: ChangeMark) -> Self::Fetch<'w> {}
+
+    #[inline]
+    unsafe fn set_archetype<'w>(_: &mut Self::Fetch<'w>, _: &Self::State, _: &'w Archetype, _: Option<&'w Table>) {}
+
+    #[inline]
+    unsafe fn fetch<'w>(_: &mut Self::Fetch<'w>, entity: Entity, _: usize) -> Self::Item<'w> {
+        entity
+    }
+}
+
+unsafe impl QueryData for Entity {
+    type ReadOnly = Self;
+}
+
+unsafe impl ReadOnlyQueryData for Entity {}
+
+unsafe impl<T: Component> WorldQuery for &T {
+    type Item<'w> = &'w T;
+    type Fetch<'w> = ComponentFetch<'w>;
+    type State = ComponentState;
+
+    #[inline]
+    fn init_state(world: &mut World) -> Self::State {
+        ComponentState::new::<T>(world)
+    }
+
+    #[inline]
+    fn matches(state: &Self::State, component_bits: &FixedBitSet) -> bool {
+        component_bits.contains(state.id.0)
+    }
+
+    #[inline]
+    fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict> {
+        access.add_component_read(state.id);
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w> {
+        ComponentFetch::new(world, state, last, current)
+    }
+
+    #[inline]
+    unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, _: &Self::State, _: &'w Archetype, table: Option<&'w Table>) {
+        fetch.set_table(table);
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: Entity, table_index: usize) -> Self::Item<'w> {
+        fetch.get::<T>(entity, table_index).deref()
+    }
+}
+
+unsafe impl<T: Component> QueryData for &T {
+    type ReadOnly = Self;
+}
+
+unsafe impl<T: Component> ReadOnlyQueryData for &T {}
+
+unsafe impl<T: Component> WorldQuery for Ref<'_, T> {
+    type Item<'w> = Ref<'w, T>;
+    type Fetch<'w> = ComponentFetch<'w>;
+    type State = ComponentState;
+
+    #[inline]
+    fn init_state(world: &mut World) -> Self::State {
+        ComponentState::new::<T>(world)
+    }
+
+    #[inline]
+    fn matches(state: &Self::State, component_bits: &FixedBitSet) -> bool {
+        component_bits.contains(state.id.0)
+    }
+
+    #[inline]
+    fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict> {
+        access.add_component_read(state.id);
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w> {
+        ComponentFetch::new(world, state, last, current)
+    }
+
+    #[inline]
+    unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, _: &Self::State, _: &'w Archetype, table: Option<&'w Table>) {
+        fetch.set_table(table);
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: Entity, table_index: usize) -> Self::Item<'w> {
+        let ticks = fetch.ticks(entity, table_index);
+        RefErased::new(
+            fetch.get::<T>(entity, table_index),
+            ticks.added(), ticks.updated(),
+            fetch.last,
+        ).casted()
+    }
+}
+
+unsafe impl<T: Component> QueryData for Ref<'_, T> {
+    type ReadOnly = Self;
+}
+
+unsafe impl<T: Component> ReadOnlyQueryData for Ref<'_, T> {}
+
+unsafe impl<T: Component> WorldQuery for &mut T {
+    type Item<'w> = Mut<'w, T>;
+    type Fetch<'w> = ComponentFetch<'w>;
+    type State = ComponentState;
+
+    #[inline]
+    fn init_state(world: &mut World) -> Self::State {
+        ComponentState::new::<T>(world)
+    }
+
+    #[inline]
+    fn matches(state: &Self::State, component_bits: &FixedBitSet) -> bool {
+        component_bits.contains(state.id.0)
+    }
+
+    #[inline]
+    fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict> {
+        access.add_component_write(state.id);
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w> {
+        ComponentFetch::new(world, state, last, current)
+    }
+
+    #[inline]
+    unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, _: &Self::State, _: &'w Archetype, table: Option<&'w Table>) {
+        fetch.set_table(table);
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: Entity, table_index: usize) -> Self::Item<'w> {
+        let ticks = fetch.ticks(entity, table_index);
+        MutErased::new(
+            fetch.get::<T>(entity, table_index).unique(),
+            &ticks.added, &ticks.updated,
+            fetch.last, fetch.current,
+        ).casted()
+    }
+}
+
+unsafe impl<'a, T: Component> QueryData for &'a mut T {
+    type ReadOnly = &'a T;
+}
+
+/// Cached data of optional queries.
+pub struct OptionFetch<'w, Q: WorldQuery> {
+    inner: Q::Fetch<'w>,
+    matches: bool,
+}
+
+unsafe impl<Q: WorldQuery> WorldQuery for Option<Q> {
+    type Item<'w> = Option<Q::Item<'w>>;
+    type Fetch<'w> = OptionFetch<'w, Q>;
+    type State = Q::State;
+
+    #[inline]
+    fn init_state(world: &mut World) -> Self::State {
+        Q::init_state(world)
+    }
+
+    #[inline]
+    fn matches(_: &Self::State, _: &FixedBitSet) -> bool {
+        true
+    }
+
+    #[inline]
+    fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict> {
+        Q::update_access(state, access)
+    }
+
+    #[inline]
+    unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w> {
+        OptionFetch {
+            inner: Q::init_fetch(world, state, last, current),
+            matches: false,
+        }
+    }
+
+    #[inline]
+    unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, state: &Self::State, archetype: &'w Archetype, table: Option<&'w Table>) {
+        fetch.matches = Q::matches(state, archetype.component_bits());
+        if fetch.matches {
+            Q::set_archetype(&mut fetch.inner, state, archetype, table);
+        }
+    }
+
+    #[inline]
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: Entity, table_index: usize) -> Self::Item<'w> {
+        fetch.matches.then(|| Q::fetch(&mut fetch.inner, entity, table_index))
+    }
+}
+
+unsafe impl<Q: QueryData> QueryData for Option<Q> {
+    type ReadOnly = Option<Q::ReadOnly>;
+}
+
+unsafe impl<Q: ReadOnlyQueryData> ReadOnlyQueryData for Option<Q> {}
+
+macro_rules! impl_query_data {
+    ($($tuple_type:ident $tuple_index:tt),*) => {
+        unsafe impl<$($tuple_type: WorldQuery,)*> WorldQuery for ($($tuple_type,)*) {
+            type Item<'w> = ($($tuple_type::Item<'w>,)*);
+            type Fetch<'w> = ($($tuple_type::Fetch<'w>,)*);
+            type State = ($($tuple_type::State,)*);
+
+            #[inline]
+            #[allow(unused, clippy::unused_unit)]
+            fn init_state(world: &mut World) -> Self::State {
+                ($($tuple_type::init_state(world),)*)
+            }
+
+            #[inline]
+            #[allow(unused)]
+            fn matches(state: &Self::State, component_bits: &FixedBitSet) -> bool {
+                true $(&& $tuple_type::matches(&state.$tuple_index, component_bits))*
+            }
+
+            #[inline]
+            #[allow(unused)]
+            fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict> {
+                $(
+                    let mut element = Access::default();
+                    $tuple_type::update_access(&state.$tuple_index, &mut element)?;
+
+                    if let Some(conflict) = access.get_conflict(&element) {
+                        return Err(conflict)
+                    }
+
+                    access.extend(&element);
+                )*
+                Ok(())
+            }
+
+            #[inline]
+            #[allow(unused, clippy::unused_unit)]
+            unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w> {
+                ($($tuple_type::init_fetch(world, &state.$tuple_index, last, current),)*)
+            }
+
+            #[inline]
+            #[allow(unused)]
+            unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, state: &Self::State, archetype: &'w Archetype, table: Option<&'w Table>) {
+                $($tuple_type::set_archetype(&mut fetch.$tuple_index, &state.$tuple_index, archetype, table);)*
+            }
+
+            #[inline]
+            #[allow(unused, clippy::unused_unit)]
+            unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: Entity, table_index: usize) -> Self::Item<'w> {
+                ($($tuple_type::fetch(&mut fetch.$tuple_index, entity, table_index),)*)
+            }
+        }
+
+        unsafe impl<$($tuple_type: QueryData,)*> QueryData for ($($tuple_type,)*) {
+            type ReadOnly = ($($tuple_type::ReadOnly,)*);
+        }
+
+        unsafe impl<$($tuple_type: ReadOnlyQueryData,)*> ReadOnlyQueryData for ($($tuple_type,)*) {}
+    }
+} impl_tuples!(impl_query_data! 8);
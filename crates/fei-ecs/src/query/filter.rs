@@ -0,0 +1,226 @@
+use fei_common::prelude::*;
+use crate::{
+    component::{
+        Component, ComponentId,
+        Archetype, Table,
+    },
+    entity::Entity,
+    query::{
+        WorldQuery,
+        ComponentState, ComponentFetch,
+    },
+    system::{
+        Access, AccessConflict,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeMark,
+};
+use fixedbitset::FixedBitSet;
+use std::marker::PhantomData;
+
+/// [`WorldQuery`]s that may be used as the filter of a [`Query`](crate::query::Query), i.e., the `F`
+/// in `Query<Q, F>`. Filters produce no items; they only decide which entities are iterated.
+///
+/// # Safety
+/// The filter must never access any component mutably.
+pub unsafe trait QueryFilter: WorldQuery {
+    /// Returns whether the entity residing in the last [set](WorldQuery::set_archetype) archetype
+    /// passes the filter.
+    ///
+    /// # Safety
+    /// Same as [`WorldQuery::fetch`].
+    unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: Entity, table_index: usize) -> bool;
+}
+
+/// [`QueryFilter`]s that decide solely by the components an archetype holds, so every entity of a
+/// matched archetype passes them. Required by [chunk iteration](crate::query::QueryChunkIter).
+///
+/// # Safety
+/// [`filter_fetch`](QueryFilter::filter_fetch) must return `true` for every entity of archetypes
+/// that [match](WorldQuery::matches) the filter.
+pub unsafe trait ArchetypeFilter: QueryFilter {}
+
+/// Only iterates entities that hold the component `T`, without fetching it.
+pub struct With<T: Component>(PhantomData<fn() -> T>);
+
+/// Only iterates entities that don't hold the component `T`.
+pub struct Without<T: Component>(PhantomData<fn() -> T>);
+
+macro_rules! impl_archetypal_filter {
+    ($name:ident $(, $not:tt)?) => {
+        unsafe impl<T: Component> WorldQuery for $name<T> {
+            type Item<'w> = ();
+            type Fetch<'w> = ();
+            type State = ComponentId;
+
+            #[inline]
+            fn init_state(world: &mut World) -> Self::State {
+                world.register_component::<T>()
+            }
+
+            #[inline]
+            fn matches(&id: &Self::State, component_bits: &FixedBitSet) -> bool {
+                $($not)? component_bits.contains(id.0)
+            }
+
+            #[inline]
+            fn update_access(_: &Self::State, _: &mut Access) -> Result<(), AccessConflict> {
+                Ok(())
+            }
+
+            #[inline]
+            unsafe fn init_fetch<'w>(_: WorldCell<'w>, _: &Self::State, _: ChangeMark, _: ChangeMark) -> Self::Fetch<'w> {}
+
+            #[inline]
+            unsafe fn set_archetype<'w>(_: &mut Self::Fetch<'w>, _: &Self::State, _: &'w Archetype, _: Option<&'w Table>) {}
+
+            #[inline]
+            unsafe fn fetch<'w>(_: &mut Self::Fetch<'w>, _: Entity, _: usize) -> Self::Item<'w> {}
+        }
+
+        unsafe impl<T: Component> QueryFilter for $name<T> {
+            #[inline]
+            unsafe fn filter_fetch(_: &mut Self::Fetch<'_>, _: Entity, _: usize) -> bool {
+                true
+            }
+        }
+    };
+}
+
+unsafe impl<T: Component> ArchetypeFilter for With<T> {}
+unsafe impl<T: Component> ArchetypeFilter for Without<T> {}
+
+impl_archetypal_filter!(With);
+impl_archetypal_filter!(Without, !);
+
+/// Only iterates entities whose component `T` has been added since the system last ran.
+pub struct Added<T: Component>(PhantomData<fn() -> T>);
+
+/// Only iterates entities whose component `T` has been added or mutably accessed since the system
+/// last ran.
+pub struct Changed<T: Component>(PhantomData<fn() -> T>);
+
+macro_rules! impl_change_filter {
+    ($name:ident, $mark:ident) => {
+        unsafe impl<T: Component> WorldQuery for $name<T> {
+            type Item<'w> = ();
+            type Fetch<'w> = ComponentFetch<'w>;
+            type State = ComponentState;
+
+            #[inline]
+            fn init_state(world: &mut World) -> Self::State {
+                ComponentState::new::<T>(world)
+            }
+
+            #[inline]
+            fn matches(state: &Self::State, component_bits: &FixedBitSet) -> bool {
+                component_bits.contains(state.id().0)
+            }
+
+            #[inline]
+            fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict> {
+                access.add_component_read(state.id());
+                Ok(())
+            }
+
+            #[inline]
+            unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w> {
+                ComponentFetch::new(world, state, last, current)
+            }
+
+            #[inline]
+            unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, _: &Self::State, _: &'w Archetype, table: Option<&'w Table>) {
+                fetch.set_table(table);
+            }
+
+            #[inline]
+            unsafe fn fetch<'w>(_: &mut Self::Fetch<'w>, _: Entity, _: usize) -> Self::Item<'w> {}
+        }
+
+        unsafe impl<T: Component> QueryFilter for $name<T> {
+            #[inline]
+            unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: Entity, table_index: usize) -> bool {
+                fetch.ticks(entity, table_index).$mark().newer_than(fetch.last)
+            }
+        }
+    };
+}
+
+impl_change_filter!(Added, added);
+impl_change_filter!(Changed, updated);
+
+/// Iterates entities that pass any of the filters in the tuple `T`, e.g. `Or<(With<A>, With<B>)>`.
+pub struct Or<T>(PhantomData<fn() -> T>);
+
+macro_rules! impl_query_filter {
+    ($($tuple_type:ident $tuple_index:tt),*) => {
+        unsafe impl<$($tuple_type: QueryFilter,)*> QueryFilter for ($($tuple_type,)*) {
+            #[inline]
+            #[allow(unused)]
+            unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: Entity, table_index: usize) -> bool {
+                true $(&& $tuple_type::filter_fetch(&mut fetch.$tuple_index, entity, table_index))*
+            }
+        }
+
+        unsafe impl<$($tuple_type: ArchetypeFilter,)*> ArchetypeFilter for ($($tuple_type,)*) {}
+    }
+} impl_tuples!(impl_query_filter! 8);
+
+macro_rules! impl_or_filter {
+    ($($tuple_type:ident $tuple_index:tt),*) => {
+        unsafe impl<$($tuple_type: QueryFilter,)*> WorldQuery for Or<($($tuple_type,)*)> {
+            type Item<'w> = ();
+            type Fetch<'w> = ($(($tuple_type::Fetch<'w>, bool),)*);
+            type State = ($($tuple_type::State,)*);
+
+            #[inline]
+            fn init_state(world: &mut World) -> Self::State {
+                ($($tuple_type::init_state(world),)*)
+            }
+
+            #[inline]
+            fn matches(state: &Self::State, component_bits: &FixedBitSet) -> bool {
+                false $(|| $tuple_type::matches(&state.$tuple_index, component_bits))*
+            }
+
+            #[inline]
+            fn update_access(state: &Self::State, access: &mut Access) -> Result<(), AccessConflict> {
+                $($tuple_type::update_access(&state.$tuple_index, access)?;)*
+                Ok(())
+            }
+
+            #[inline]
+            unsafe fn init_fetch<'w>(world: WorldCell<'w>, state: &Self::State, last: ChangeMark, current: ChangeMark) -> Self::Fetch<'w> {
+                ($(($tuple_type::init_fetch(world, &state.$tuple_index, last, current), false),)*)
+            }
+
+            #[inline]
+            unsafe fn set_archetype<'w>(fetch: &mut Self::Fetch<'w>, state: &Self::State, archetype: &'w Archetype, table: Option<&'w Table>) {
+                $(
+                    let (inner, matches) = &mut fetch.$tuple_index;
+                    *matches = $tuple_type::matches(&state.$tuple_index, archetype.component_bits());
+                    if *matches {
+                        $tuple_type::set_archetype(inner, &state.$tuple_index, archetype, table);
+                    }
+                )*
+            }
+
+            #[inline]
+            unsafe fn fetch<'w>(_: &mut Self::Fetch<'w>, _: Entity, _: usize) -> Self::Item<'w> {}
+        }
+
+        unsafe impl<$($tuple_type: QueryFilter,)*> QueryFilter for Or<($($tuple_type,)*)> {
+            #[inline]
+            unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: Entity, table_index: usize) -> bool {
+                false $(|| {
+                    let (inner, matches) = &mut fetch.$tuple_index;
+                    *matches && $tuple_type::filter_fetch(inner, entity, table_index)
+                })*
+            }
+        }
+
+        unsafe impl<$($tuple_type: ArchetypeFilter,)*> ArchetypeFilter for Or<($($tuple_type,)*)> {}
+    }
+} impl_tuples!(impl_or_filter! 1 8);
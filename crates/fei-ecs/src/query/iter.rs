@@ -0,0 +1,186 @@
+use fei_common::prelude::*;
+use crate::{
+    component::{
+        Archetype, ArchetypeId,
+        Components,
+    },
+    entity::{
+        Entity,
+        Entities,
+    },
+    query::{
+        QueryData, QueryFilter,
+    },
+    world::WorldCell,
+    ChangeMark,
+};
+use std::slice::Iter;
+
+/// Iterates entities matching a query, archetype by archetype.
+pub struct QueryIter<'w, 's, Q: QueryData, F: QueryFilter = ()> {
+    components: &'w Components,
+    entities: &'w Entities,
+
+    data_state: &'s Q::State,
+    filter_state: &'s F::State,
+    data: Q::Fetch<'w>,
+    filter: F::Fetch<'w>,
+
+    archetypes: Iter<'s, ArchetypeId>,
+    archetype_entities: Iter<'w, Entity>,
+}
+
+impl<'w, 's, Q: QueryData, F: QueryFilter> QueryIter<'w, 's, Q, F> {
+    /// # Safety
+    /// - `archetypes` must only contain IDs of archetypes in `world` matching both `Q` and `F`.
+    /// - There may not be other conflicting accesses to the components `Q` and `F` access.
+    #[inline]
+    pub(crate) unsafe fn new(
+        world: WorldCell<'w>, archetypes: &'s [ArchetypeId],
+        data_state: &'s Q::State, filter_state: &'s F::State,
+        last: ChangeMark, current: ChangeMark,
+    ) -> Self {
+        Self {
+            components: world.components(),
+            entities: world.entities(),
+
+            data_state,
+            filter_state,
+            data: Q::init_fetch(world, data_state, last, current),
+            filter: F::init_fetch(world, filter_state, last, current),
+
+            archetypes: archetypes.iter(),
+            archetype_entities: [].iter(),
+        }
+    }
+}
+
+impl<'w, 's, Q: QueryData, F: QueryFilter> Iterator for QueryIter<'w, 's, Q, F> {
+    type Item = Q::Item<'w>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&entity) = self.archetype_entities.next() {
+                // Safety: Entities residing in archetypes always have a location.
+                let location = unsafe { self.entities.location(entity).unwrap_unchecked() };
+                let table_index = location.table_index.unwrap_or_default();
+
+                // Safety: The fetches have been set to the archetype the entity resides in.
+                unsafe {
+                    if F::filter_fetch(&mut self.filter, entity, table_index) {
+                        return Some(Q::fetch(&mut self.data, entity, table_index))
+                    }
+                }
+            } else {
+                let &id = self.archetypes.next()?;
+
+                // Safety: Archetype and table IDs are always valid, and the archetype matches.
+                unsafe {
+                    let archetype = self.components.archetypes().get_unchecked(id.0);
+                    let table = archetype.table_id().map(|id| self.components.tables().get_unchecked(id.0));
+
+                    Q::set_archetype(&mut self.data, self.data_state, archetype, table);
+                    F::set_archetype(&mut self.filter, self.filter_state, archetype, table);
+                    self.archetype_entities = archetype.entities().iter();
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+/// Iterates entities matching a query in parallel, splitting the entities of each matched archetype
+/// into batches run on the [`rayon`] thread pool. Each batch occupies its own rows of the
+/// archetype's table, so mutable fetches never alias across batches.
+pub struct QueryParIter<'w, 's, Q: QueryData, F: QueryFilter = ()> {
+    world: WorldCell<'w>,
+    archetypes: &'s [ArchetypeId],
+    data_state: &'s Q::State,
+    filter_state: &'s F::State,
+    last: ChangeMark,
+    current: ChangeMark,
+    batch_size: usize,
+}
+
+impl<'w, 's, Q: QueryData, F: QueryFilter> QueryParIter<'w, 's, Q, F> {
+    /// The maximum number of entities each batch holds, unless [configured](QueryParIter::batch_size)
+    /// otherwise.
+    pub const DEFAULT_BATCH_SIZE: usize = 1024;
+
+    /// # Safety
+    /// Same as [`QueryIter::new`].
+    #[inline]
+    pub(crate) unsafe fn new(
+        world: WorldCell<'w>, archetypes: &'s [ArchetypeId],
+        data_state: &'s Q::State, filter_state: &'s F::State,
+        last: ChangeMark, current: ChangeMark,
+    ) -> Self {
+        Self {
+            world,
+            archetypes,
+            data_state,
+            filter_state,
+            last,
+            current,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of entities each batch holds. Smaller batches balance uneven workloads
+    /// better, at the cost of more scheduling overhead.
+    ///
+    /// # Panics
+    /// Panics if `batch_size` is zero.
+    #[inline]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        assert_ne!(batch_size, 0, "batch size must be non-zero");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Runs `func` on every entity, blocking until all batches are done.
+    pub fn for_each(self, func: impl Fn(Q::Item<'w>) + Send + Sync) {
+        // Safety: Archetypes are only read to split them into batches.
+        let components = unsafe { self.world.components() };
+        let (this, func) = (&self, &func);
+
+        rayon::in_place_scope(|scope| {
+            for &id in self.archetypes {
+                // Safety: Archetype IDs are always valid.
+                let archetype = unsafe { components.archetypes().get_unchecked(id.0) };
+                for batch in archetype.entities().chunks(self.batch_size) {
+                    // Safety: Batches hold disjoint entities of a matching archetype.
+                    scope.spawn(move |_| unsafe { this.for_each_in(archetype, batch, func) });
+                }
+            }
+        });
+    }
+
+    /// # Safety
+    /// `archetype` must match the query, and `entities` must reside in it without being fetched by
+    /// other batches.
+    #[inline]
+    unsafe fn for_each_in(&self, archetype: &'w Archetype, entities: &'w [Entity], func: &impl Fn(Q::Item<'w>)) {
+        let components = self.world.components();
+        let table = archetype.table_id().map(|id| components.tables().get_unchecked(id.0));
+
+        let mut data = Q::init_fetch(self.world, self.data_state, self.last, self.current);
+        let mut filter = F::init_fetch(self.world, self.filter_state, self.last, self.current);
+        Q::set_archetype(&mut data, self.data_state, archetype, table);
+        F::set_archetype(&mut filter, self.filter_state, archetype, table);
+
+        let locations = self.world.entities();
+        for &entity in entities {
+            // Safety: Entities residing in archetypes always have a location.
+            let table_index = locations.location(entity).unwrap_unchecked().table_index.unwrap_or_default();
+            if F::filter_fetch(&mut filter, entity, table_index) {
+                func(Q::fetch(&mut data, entity, table_index));
+            }
+        }
+    }
+}
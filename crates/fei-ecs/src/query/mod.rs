@@ -0,0 +1,320 @@
+mod chunk;
+mod dynamic;
+mod fetch;
+mod filter;
+mod iter;
+mod param;
+mod state;
+
+pub use chunk::*;
+pub use dynamic::*;
+pub use fetch::*;
+pub use filter::*;
+pub use iter::*;
+pub use param::*;
+pub use state::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_common::prelude::*;
+    use fei_ecs_macros::Component;
+    use crate::{
+        component::ComponentId,
+        entity::Entity,
+        system::{
+            IntoSystem, System,
+        },
+        world::World,
+        ChangeAware, Ref,
+    };
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Position(f32);
+    #[derive(Component, Debug, PartialEq)]
+    #[component(storage = "SparseSet")]
+    struct Velocity(f32);
+    #[derive(Component, Debug, PartialEq)]
+    struct Frozen;
+
+    #[test]
+    fn iterate() -> anyhow::Result<()> {
+        let mut world = World::default();
+        let a = world.spawn((Position(0.0), Velocity(1.0)))?.id();
+        let b = world.spawn((Position(1.0), Velocity(-1.0), Frozen))?.id();
+        let c = world.spawn(Position(2.0))?.id();
+        let d = world.spawn(Velocity(3.0))?.id();
+
+        let mut query = world.query::<(Entity, &mut Position, &Velocity, Option<&Frozen>)>();
+        for (_, mut pos, vel, frozen) in query.iter_mut(&mut world) {
+            if frozen.is_none() {
+                pos.0 += vel.0;
+            }
+        }
+
+        let mut entities = query.iter(&world).map(|(entity, ..)| entity).collect::<Vec<_>>();
+        entities.sort_by_key(|entity| entity.id());
+        assert_eq!(entities, [a, b]);
+
+        let mut positions = world.query::<&Position>();
+        assert_eq!(positions.get(&world, a), Ok(&Position(1.0)));
+        assert_eq!(positions.get(&world, b), Ok(&Position(1.0)));
+        assert_eq!(positions.get(&world, c), Ok(&Position(2.0)));
+        assert_eq!(positions.get(&world, d), Err(QueryEntityError::Mismatch));
+
+        world.sync_change_mark();
+        let mut refs = world.query::<Ref<Position>>();
+        assert!(refs.iter(&world).all(|pos| !pos.is_added() && !pos.is_updated()));
+        Ok(())
+    }
+
+    #[test]
+    fn system_param() -> anyhow::Result<()> {
+        fn movement(mut query: Query<(&mut Position, &Velocity)>) -> anyhow::Result<usize> {
+            let mut count = 0;
+            for (mut pos, vel) in &mut query {
+                pos.0 += vel.0;
+                count += 1;
+            }
+
+            Ok(count)
+        }
+
+        fn sum(query: Query<&Position>) -> anyhow::Result<f32> {
+            Ok(query.iter().map(|pos| pos.0).sum())
+        }
+
+        let mut world = World::default();
+        let mut movement = movement.into_system(&mut world)?;
+        let mut sum = sum.into_system(&mut world)?;
+
+        // Archetypes created after the system has been initialized must be matched too.
+        world.spawn((Position(0.0), Velocity(2.0)))?;
+        world.spawn((Position(1.0), Velocity(3.0), Frozen))?;
+        world.spawn(Position(4.0))?;
+
+        assert_eq!(movement.call((), &mut world)?, 2);
+        assert_eq!(sum.call((), &mut world)?, 10.0);
+        Ok(())
+    }
+
+    #[test]
+    fn filters() -> anyhow::Result<()> {
+        fn thawed(query: Query<Entity, (With<Velocity>, Without<Frozen>)>) -> anyhow::Result<Vec<Entity>> {
+            Ok(query.iter().collect())
+        }
+
+        type Either = Or<(With<Velocity>, With<Frozen>)>;
+        fn either(query: Query<Entity, Either>) -> anyhow::Result<usize> {
+            Ok(query.iter().count())
+        }
+
+        fn added(query: Query<Entity, Added<Position>>) -> anyhow::Result<Vec<Entity>> {
+            Ok(query.iter().collect())
+        }
+
+        fn changed(query: Query<Entity, Changed<Position>>) -> anyhow::Result<Vec<Entity>> {
+            Ok(query.iter().collect())
+        }
+
+        fn movement(mut query: Query<(&mut Position, &Velocity), Without<Frozen>>) -> anyhow::Result<()> {
+            for (mut pos, vel) in &mut query {
+                pos.0 += vel.0;
+            }
+
+            Ok(())
+        }
+
+        let mut world = World::default();
+        let mut thawed = thawed.into_system(&mut world)?;
+        let mut either = either.into_system(&mut world)?;
+        let mut added = added.into_system(&mut world)?;
+        let mut changed = changed.into_system(&mut world)?;
+        let mut movement = movement.into_system(&mut world)?;
+
+        let a = world.spawn((Position(0.0), Velocity(1.0)))?.id();
+        let b = world.spawn((Position(0.0), Velocity(1.0), Frozen))?.id();
+        let c = world.spawn((Position(0.0), Frozen))?.id();
+        world.spawn(Position(0.0))?;
+
+        assert_eq!(thawed.call((), &mut world)?, [a]);
+        assert_eq!(either.call((), &mut world)?, 3);
+
+        assert_eq!(added.call((), &mut world)?.len(), 4);
+        assert_eq!(changed.call((), &mut world)?.len(), 4);
+        assert!(added.call((), &mut world)?.is_empty());
+        assert!(changed.call((), &mut world)?.is_empty());
+
+        movement.call((), &mut world)?;
+        assert!(added.call((), &mut world)?.is_empty());
+        assert_eq!(changed.call((), &mut world)?, [a]);
+
+        world.view_mut(c)?.insert(Position(1.0));
+        world.view_mut(b)?.remove::<Position>();
+        world.view_mut(b)?.insert(Position(1.0));
+        assert_eq!(added.call((), &mut world)?, [b]);
+
+        let mut changed = changed.call((), &mut world)?;
+        changed.sort_by_key(|entity| entity.id());
+        assert_eq!(changed, [b, c]);
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic() -> anyhow::Result<()> {
+        let mut world = World::default();
+        let a = world.spawn((Position(0.0), Velocity(1.0)))?.id();
+        let b = world.spawn((Position(1.0), Velocity(2.0), Frozen))?.id();
+        let c = world.spawn(Position(2.0))?.id();
+
+        let position = world.register_component::<Position>();
+        let velocity = world.register_component::<Velocity>();
+        let frozen = world.register_component::<Frozen>();
+
+        let mut query = world.query_builder().write(position).optional_read(velocity).without(frozen).build()?;
+        for mut item in query.iter_mut(&mut world) {
+            assert_eq!(item.len(), 2);
+            assert!(item.get_mut(1).is_none());
+
+            let vel = item.get(1).map_or(0.0, |vel| unsafe { vel.deref::<Velocity>().0 });
+            unsafe { item.get_mut(0).unwrap().deref_mut::<Position>().0 += vel };
+        }
+
+        let mut entities = query.iter(&world).map(|item| item.entity()).collect::<Vec<_>>();
+        entities.sort_by_key(|entity| entity.id());
+        assert_eq!(entities, [a, c]);
+
+        assert_eq!(world.view(a)?.get::<Position>().map(|pos| pos.0), Some(1.0));
+        assert_eq!(world.view(b)?.get::<Position>().map(|pos| pos.0), Some(1.0));
+        assert_eq!(world.view(c)?.get::<Position>().map(|pos| pos.0), Some(2.0));
+
+        // Building only borrows the world immutably, like iterating.
+        let shared = &world;
+        let mut query = shared.query_builder().read(frozen).with(velocity).build()?;
+        assert_eq!(query.iter(shared).map(|item| item.entity()).collect::<Vec<_>>(), [b]);
+
+        assert!(matches!(world.query_builder().read(position).write(position).build(), Err(QueryBuildError::Conflict(..))));
+        assert!(matches!(world.query_builder().read(ComponentId(usize::MAX)).build(), Err(QueryBuildError::Unregistered(..))));
+        Ok(())
+    }
+
+    #[test]
+    fn incremental() -> anyhow::Result<()> {
+        let mut world = World::default();
+        world.spawn(Position(0.0))?;
+
+        let mut query = world.query::<&Position>();
+        let generation = query.archetype_generation();
+        assert_eq!(generation, world.components().archetype_generation());
+        assert_eq!(query.archetypes().len(), 1);
+
+        // Entities of already-matched archetypes don't require a rematch.
+        world.spawn(Position(1.0))?;
+        query.update_archetypes(&world);
+        assert_eq!(query.archetype_generation(), generation);
+        assert_eq!(query.iter(&world).count(), 2);
+
+        // Archetypes sharing a table don't duplicate it.
+        world.spawn((Position(2.0), Velocity(0.0)))?;
+        world.spawn(Velocity(0.0))?;
+        assert_eq!(query.iter(&world).count(), 3);
+        assert!(query.archetype_generation() > generation);
+        assert_eq!(query.archetypes().len(), 2);
+        assert_eq!(query.tables().len(), 1);
+
+        // Changing the filter rematches every archetype.
+        let frozen = world.register_component::<Frozen>();
+        world.spawn((Position(3.0), Frozen))?;
+        let mut query = query.without_id(frozen);
+        assert!(query.archetypes().is_empty());
+        assert_eq!(query.iter(&world).count(), 3);
+        assert_eq!(query.archetypes().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn parallel() -> anyhow::Result<()> {
+        use fei_ecs_macros::Resource;
+        use crate::resource::Res;
+        use std::sync::atomic::{
+            AtomicUsize, Ordering,
+        };
+
+        fn movement(mut query: Query<(&mut Position, &Velocity), Without<Frozen>>) -> anyhow::Result<()> {
+            query.par_iter_mut().batch_size(64).for_each(|(mut pos, vel)| pos.0 += vel.0);
+            Ok(())
+        }
+
+        let mut world = World::default();
+        for i in 0..1000 {
+            match i % 3 {
+                0 => world.spawn((Position(0.0), Velocity(1.0)))?,
+                1 => world.spawn((Position(0.0), Velocity(1.0), Frozen))?,
+                _ => world.spawn(Position(0.0))?,
+            };
+        }
+
+        let mut movement = movement.into_system(&mut world)?;
+        movement.call((), &mut world)?;
+        movement.call((), &mut world)?;
+
+        let sum = world.query::<&Position>().iter(&world).map(|pos| pos.0).sum::<f32>();
+        assert_eq!(sum, 334.0 * 2.0);
+
+        fn count(query: Query<&Position, Changed<Position>>, counter: Res<Counter>) -> anyhow::Result<()> {
+            let counter = &counter.0;
+            query.par_for_each(|_| { counter.fetch_add(1, Ordering::Relaxed); });
+            Ok(())
+        }
+
+        #[derive(Resource, Default)]
+        struct Counter(AtomicUsize);
+
+        world.init_res::<Counter>();
+        count.into_system(&mut world)?.call((), &mut world)?;
+        assert_eq!(world.res::<Counter>().unwrap().0.load(Ordering::Relaxed), 1000);
+        Ok(())
+    }
+
+    #[test]
+    fn chunks() -> anyhow::Result<()> {
+        #[derive(Component)]
+        struct Acceleration(f32);
+
+        fn accelerate(mut query: Query<(Entity, &mut Position, &Acceleration), Without<Frozen>>) -> anyhow::Result<Vec<Vec<Entity>>> {
+            let mut chunks = Vec::new();
+            for (entities, positions, accelerations) in query.iter_chunks_mut() {
+                for (pos, acc) in positions.iter_mut().zip(accelerations) {
+                    pos.0 += acc.0;
+                }
+
+                chunks.push(entities.to_vec());
+            }
+
+            Ok(chunks)
+        }
+
+        let mut world = World::default();
+        let a = world.spawn((Position(0.0), Acceleration(1.0)))?.id();
+        let b = world.spawn((Position(0.0), Acceleration(2.0), Frozen))?.id();
+        let c = world.spawn((Position(0.0), Acceleration(3.0)))?.id();
+        let d = world.spawn(Position(5.0))?.id();
+
+        // `b` shares the table with `a` and `c`, splitting it into two chunks.
+        let mut accelerate = accelerate.into_system(&mut world)?;
+        assert_eq!(accelerate.call((), &mut world)?, [[a], [c]]);
+
+        let mut query = world.query::<(Entity, &Position)>();
+        let chunks = query.iter_chunks(&world).map(|(entities, positions)| (
+            entities.to_vec(),
+            positions.iter().map(|pos| pos.0).collect::<Vec<_>>(),
+        )).collect::<Vec<_>>();
+        assert_eq!(chunks, [(vec![a, b, c], vec![1.0, 0.0, 3.0]), (vec![d], vec![5.0])]);
+
+        let mut changed = world.query_filtered::<Entity, Changed<Position>>();
+        world.sync_change_mark();
+        accelerate.call((), &mut world)?;
+        assert_eq!(changed.iter(&world).collect::<Vec<_>>(), [a, c]);
+        Ok(())
+    }
+}
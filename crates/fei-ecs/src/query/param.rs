@@ -0,0 +1,144 @@
+use fei_common::prelude::*;
+use crate::{
+    entity::Entity,
+    query::{
+        WorldQuery, QueryData, ReadOnlyQueryData, QueryFilter,
+        ChunkQueryData, ArchetypeFilter,
+        QueryState, QueryIter, QueryParIter, QueryChunkIter, QueryEntityError,
+    },
+    system::{
+        SystemParam, ReadOnlySystemParam,
+        Access, AccessConflictError,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeMark,
+};
+use std::any::type_name;
+
+/// A [`SystemParam`] iterating entities that hold the components `Q` fetches, filtered by `F`.
+pub struct Query<'w, 's, Q: QueryData, F: QueryFilter = ()> {
+    world: WorldCell<'w>,
+    state: &'s QueryState<Q::ReadOnly, F>,
+    last: ChangeMark,
+    current: ChangeMark,
+}
+
+impl<'w, 's, Q: QueryData, F: QueryFilter> Query<'w, 's, Q, F> {
+    #[inline]
+    pub fn iter(&self) -> QueryIter<'_, 's, Q::ReadOnly, F> {
+        unsafe { self.state.iter_unchecked(self.world, self.last, self.current) }
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> QueryIter<'_, 's, Q, F> {
+        unsafe { self.state.iter_unchecked(self.world, self.last, self.current) }
+    }
+
+    /// Iterates in parallel, in batches of table rows. See [`QueryParIter`].
+    #[inline]
+    pub fn par_iter(&self) -> QueryParIter<'_, 's, Q::ReadOnly, F> {
+        unsafe { self.state.par_iter_unchecked(self.world, self.last, self.current) }
+    }
+
+    #[inline]
+    pub fn par_iter_mut(&mut self) -> QueryParIter<'_, 's, Q, F> {
+        unsafe { self.state.par_iter_unchecked(self.world, self.last, self.current) }
+    }
+
+    /// Runs `func` on every entity in parallel with the [default batch size](QueryParIter::DEFAULT_BATCH_SIZE).
+    #[inline]
+    pub fn par_for_each(&self, func: impl Fn(<Q::ReadOnly as WorldQuery>::Item<'_>) + Send + Sync) {
+        self.par_iter().for_each(func)
+    }
+
+    #[inline]
+    pub fn par_for_each_mut(&mut self, func: impl Fn(Q::Item<'_>) + Send + Sync) {
+        self.par_iter_mut().for_each(func)
+    }
+
+    /// Iterates as contiguous slices of table columns. See [`QueryChunkIter`].
+    ///
+    /// # Panics
+    /// Panics if the query isn't [dense](ChunkQueryData::is_dense).
+    #[inline]
+    pub fn iter_chunks(&self) -> QueryChunkIter<'_, 's, Q::ReadOnly, F>
+    where
+        Q::ReadOnly: ChunkQueryData,
+        F: ArchetypeFilter,
+    {
+        unsafe { self.state.iter_chunks_unchecked(self.world, self.current) }
+    }
+
+    /// # Panics
+    /// Panics if the query isn't [dense](ChunkQueryData::is_dense).
+    #[inline]
+    pub fn iter_chunks_mut(&mut self) -> QueryChunkIter<'_, 's, Q, F>
+    where
+        Q: ChunkQueryData,
+        F: ArchetypeFilter,
+    {
+        unsafe { self.state.iter_chunks_unchecked(self.world, self.current) }
+    }
+
+    #[inline]
+    pub fn get(&self, entity: Entity) -> Result<<Q::ReadOnly as WorldQuery>::Item<'_>, QueryEntityError> {
+        unsafe { self.state.get_unchecked::<Q::ReadOnly>(self.world, entity, self.last, self.current) }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, entity: Entity) -> Result<Q::Item<'_>, QueryEntityError> {
+        unsafe { self.state.get_unchecked::<Q>(self.world, entity, self.last, self.current) }
+    }
+}
+
+impl<'a, 'w, 's, Q: QueryData, F: QueryFilter> IntoIterator for &'a Query<'w, 's, Q, F> {
+    type Item = <Q::ReadOnly as WorldQuery>::Item<'a>;
+    type IntoIter = QueryIter<'a, 's, Q::ReadOnly, F>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, 'w, 's, Q: QueryData, F: QueryFilter> IntoIterator for &'a mut Query<'w, 's, Q, F> {
+    type Item = Q::Item<'a>;
+    type IntoIter = QueryIter<'a, 's, Q, F>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+unsafe impl<'w, 's, Q: ReadOnlyQueryData + 'static, F: QueryFilter + 'static> ReadOnlySystemParam for Query<'w, 's, Q, F> {}
+impl<'w, 's, Q: QueryData + 'static, F: QueryFilter + 'static> SystemParam for Query<'w, 's, Q, F> {
+    type State = QueryState<Q::ReadOnly, F>;
+    type Item<'w2, 's2> = Query<'w2, 's2, Q, F>;
+    type ReadOnly = Query<'w, 's, Q::ReadOnly, F>;
+
+    /// Matches archetypes created since the system last ran against the query.
+    #[inline]
+    unsafe fn construct<'w2, 's2>(world: WorldCell<'w2>, state: &'s2 mut Self::State, last: ChangeMark, current: ChangeMark) -> anyhow::Result<Self::Item<'w2, 's2>> {
+        state.update_archetypes(world.get());
+        Ok(Query {
+            world,
+            state,
+            last,
+            current,
+        })
+    }
+
+    #[inline]
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        let state = QueryState::new(world);
+        state.update_access::<Q>(access).map_err(|conflict| AccessConflictError::Query {
+            query: type_name::<Self>(),
+            target: conflict.describe(world),
+        })?;
+
+        Ok(state)
+    }
+}
@@ -0,0 +1,314 @@
+use fei_common::prelude::*;
+use crate::{
+    component::{
+        ComponentId,
+        ArchetypeId, ArchetypeGeneration, TableId,
+    },
+    entity::Entity,
+    query::{
+        WorldQuery, QueryData, QueryFilter,
+        ChunkQueryData, ArchetypeFilter,
+        QueryIter, QueryParIter, QueryChunkIter,
+    },
+    system::{
+        Access, AccessConflict,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeMark,
+};
+use fixedbitset::FixedBitSet;
+use std::marker::PhantomData;
+
+#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum QueryEntityError {
+    #[error("entity does not exist")]
+    Nonexistent,
+    #[error("entity does not match the query")]
+    Mismatch,
+}
+
+/// Persistent state of a query, holding the fetch states and the archetypes and tables matching
+/// them. Only archetypes created since the last [update](QueryState::update_archetypes) are matched
+/// on each update.
+pub struct QueryState<Q: QueryData, F: QueryFilter = ()> {
+    data: Q::State,
+    filter: F::State,
+    with: FixedBitSet,
+    without: FixedBitSet,
+    generation: ArchetypeGeneration,
+    archetypes: Vec<ArchetypeId>,
+    archetype_bits: FixedBitSet,
+    tables: Vec<TableId>,
+    table_bits: FixedBitSet,
+    partial_tables: FixedBitSet,
+    _marker: PhantomData<fn() -> (Q, F)>,
+}
+
+impl<Q: QueryData, F: QueryFilter> QueryState<Q, F> {
+    #[inline]
+    pub fn new(world: &mut World) -> Self {
+        let data = Q::init_state(world);
+        let filter = F::init_state(world);
+        Self::from_state(world, data, filter)
+    }
+
+    /// Creates the query from already-initialized fetch states, e.g. ones assembled by a
+    /// [`QueryBuilder`](crate::query::QueryBuilder).
+    #[inline]
+    pub(crate) fn from_state(world: &World, data: Q::State, filter: F::State) -> Self {
+        let mut state = Self {
+            data,
+            filter,
+            with: FixedBitSet::new(),
+            without: FixedBitSet::new(),
+            generation: ArchetypeGeneration::default(),
+            archetypes: Vec::new(),
+            archetype_bits: FixedBitSet::new(),
+            tables: Vec::new(),
+            table_bits: FixedBitSet::new(),
+            partial_tables: FixedBitSet::new(),
+            _marker: PhantomData,
+        };
+
+        state.update_archetypes(world);
+        state
+    }
+
+    /// Matches archetypes created since the last update against this query.
+    pub fn update_archetypes(&mut self, world: &World) {
+        let components = world.components();
+        let generation = components.archetype_generation();
+        if generation == self.generation { return }
+
+        let archetypes = components.archetypes();
+        self.archetype_bits.grow(archetypes.len());
+        self.table_bits.grow(components.tables().len());
+        self.partial_tables.grow(components.tables().len());
+
+        for (index, archetype) in archetypes.iter().enumerate().skip(self.generation.0) {
+            let bits = archetype.component_bits();
+            if
+                Q::matches(&self.data, bits) && F::matches(&self.filter, bits) &&
+                self.with.is_subset(bits) && self.without.is_disjoint(bits)
+            {
+                self.archetypes.push(ArchetypeId(index));
+                self.archetype_bits.insert(index);
+
+                if let Some(table_id) = archetype.table_id() {
+                    if !self.table_bits.put(table_id.0) {
+                        self.tables.push(table_id);
+                    }
+                }
+            } else if let Some(table_id) = archetype.table_id() {
+                self.partial_tables.insert(table_id.0);
+            }
+        }
+
+        self.generation = generation;
+    }
+
+    /// Forgets every matched archetype, so that the next [update](QueryState::update_archetypes)
+    /// matches all of them again.
+    #[inline]
+    fn reset_archetypes(&mut self) {
+        self.generation = ArchetypeGeneration::default();
+        self.archetypes.clear();
+        self.archetype_bits.clear();
+        self.tables.clear();
+        self.table_bits.clear();
+        self.partial_tables.clear();
+    }
+
+    /// Additionally requires matched archetypes to hold the component, e.g. one registered from a
+    /// [`ComponentDescriptor`](crate::component::ComponentDescriptor). Takes effect on the next
+    /// [update](QueryState::update_archetypes).
+    #[inline]
+    pub fn with_id(mut self, id: ComponentId) -> Self {
+        self.with.grow(id.0 + 1);
+        self.with.insert(id.0);
+        self.reset_archetypes();
+        self
+    }
+
+    /// Additionally requires matched archetypes to not hold the component. Takes effect on the next
+    /// [update](QueryState::update_archetypes).
+    #[inline]
+    pub fn without_id(mut self, id: ComponentId) -> Self {
+        self.without.grow(id.0 + 1);
+        self.without.insert(id.0);
+        self.reset_archetypes();
+        self
+    }
+
+    /// Registers the components `D` and `F` access, failing if `D` conflicts with itself. `D` may be
+    /// `Q` itself or its [non-read-only](QueryData) counterpart sharing the same state.
+    #[inline]
+    pub fn update_access<D: QueryData<State = Q::State>>(&self, access: &mut Access) -> Result<(), AccessConflict> {
+        let mut query = Access::default();
+        D::update_access(&self.data, &mut query)?;
+        F::update_access(&self.filter, &mut query)?;
+
+        access.extend(&query);
+        Ok(())
+    }
+
+    /// Returns the IDs of all archetypes matching this query, as of the last
+    /// [update](QueryState::update_archetypes).
+    #[inline]
+    pub fn archetypes(&self) -> &[ArchetypeId] {
+        &self.archetypes
+    }
+
+    /// Returns the IDs of the tables of all archetypes matching this query, without duplicates.
+    /// Archetypes with only sparse-set components don't refer to a table.
+    #[inline]
+    pub fn tables(&self) -> &[TableId] {
+        &self.tables
+    }
+
+    /// Returns the archetype generation this query has been [updated](QueryState::update_archetypes)
+    /// to.
+    #[inline]
+    pub fn archetype_generation(&self) -> ArchetypeGeneration {
+        self.generation
+    }
+
+    #[inline]
+    pub fn iter<'w, 's>(&'s mut self, world: &'w World) -> QueryIter<'w, 's, Q::ReadOnly, F> {
+        self.update_archetypes(world);
+        unsafe { self.iter_unchecked(world.cell(), world.last_change_mark(), world.read_change_mark()) }
+    }
+
+    #[inline]
+    pub fn iter_mut<'w, 's>(&'s mut self, world: &'w mut World) -> QueryIter<'w, 's, Q, F> {
+        self.update_archetypes(world);
+        let last = world.last_change_mark();
+        let current = world.change_mark_mut();
+        unsafe { self.iter_unchecked(WorldCell::write(world), last, current) }
+    }
+
+    /// Iterates as contiguous slices of table columns. See [`QueryChunkIter`].
+    ///
+    /// # Panics
+    /// Panics if the query isn't [dense](ChunkQueryData::is_dense).
+    #[inline]
+    pub fn iter_chunks<'w, 's>(&'s mut self, world: &'w World) -> QueryChunkIter<'w, 's, Q::ReadOnly, F>
+    where
+        Q::ReadOnly: ChunkQueryData,
+        F: ArchetypeFilter,
+    {
+        self.update_archetypes(world);
+        unsafe { self.iter_chunks_unchecked(world.cell(), world.read_change_mark()) }
+    }
+
+    /// # Panics
+    /// Panics if the query isn't [dense](ChunkQueryData::is_dense).
+    #[inline]
+    pub fn iter_chunks_mut<'w, 's>(&'s mut self, world: &'w mut World) -> QueryChunkIter<'w, 's, Q, F>
+    where
+        Q: ChunkQueryData,
+        F: ArchetypeFilter,
+    {
+        self.update_archetypes(world);
+        let current = world.change_mark_mut();
+        unsafe { self.iter_chunks_unchecked(WorldCell::write(world), current) }
+    }
+
+    #[inline]
+    pub fn get<'w>(&mut self, world: &'w World, entity: Entity) -> Result<<Q::ReadOnly as WorldQuery>::Item<'w>, QueryEntityError> {
+        self.update_archetypes(world);
+        unsafe { self.get_unchecked::<Q::ReadOnly>(world.cell(), entity, world.last_change_mark(), world.read_change_mark()) }
+    }
+
+    #[inline]
+    pub fn get_mut<'w>(&mut self, world: &'w mut World, entity: Entity) -> Result<Q::Item<'w>, QueryEntityError> {
+        self.update_archetypes(world);
+        let last = world.last_change_mark();
+        let current = world.change_mark_mut();
+        unsafe { self.get_unchecked::<Q>(WorldCell::write(world), entity, last, current) }
+    }
+
+    /// Iterates the world without checking for access conflicts. `D` may be `Q` itself or its
+    /// [read-only](QueryData::ReadOnly) variant.
+    ///
+    /// # Safety
+    /// - `world` must be the world this state is created from, with archetypes already
+    ///   [updated](QueryState::update_archetypes).
+    /// - There may not be other conflicting accesses to the components `D` and `F` access.
+    #[inline]
+    pub unsafe fn iter_unchecked<'w, 's, D: QueryData<State = Q::State>>(
+        &'s self, world: WorldCell<'w>,
+        last: ChangeMark, current: ChangeMark,
+    ) -> QueryIter<'w, 's, D, F> {
+        QueryIter::new(world, &self.archetypes, &self.data, &self.filter, last, current)
+    }
+
+    /// Iterates the world in parallel without checking for access conflicts. `D` may be `Q` itself or
+    /// its [read-only](QueryData::ReadOnly) variant.
+    ///
+    /// # Safety
+    /// Same as [`iter_unchecked`](QueryState::iter_unchecked).
+    #[inline]
+    pub unsafe fn par_iter_unchecked<'w, 's, D: QueryData<State = Q::State>>(
+        &'s self, world: WorldCell<'w>,
+        last: ChangeMark, current: ChangeMark,
+    ) -> QueryParIter<'w, 's, D, F> {
+        QueryParIter::new(world, &self.archetypes, &self.data, &self.filter, last, current)
+    }
+
+    /// Iterates the world as contiguous slices of table columns without checking for access conflicts.
+    /// `D` may be `Q` itself or its [read-only](QueryData::ReadOnly) variant.
+    ///
+    /// # Safety
+    /// Same as [`iter_unchecked`](QueryState::iter_unchecked).
+    ///
+    /// # Panics
+    /// Panics if `D` isn't [dense](ChunkQueryData::is_dense).
+    #[inline]
+    pub unsafe fn iter_chunks_unchecked<'w, 's, D: ChunkQueryData<State = Q::State>>(
+        &'s self, world: WorldCell<'w>, current: ChangeMark,
+    ) -> QueryChunkIter<'w, 's, D, F>
+    where
+        F: ArchetypeFilter,
+    {
+        assert!(D::is_dense(&self.data), "chunk iteration requires every component to be stored in tables");
+        QueryChunkIter::new(world, &self.data, &self.tables, &self.archetype_bits, &self.partial_tables, current)
+    }
+
+    /// Fetches an entity without checking for access conflicts. `D` may be `Q` itself or its
+    /// [read-only](QueryData::ReadOnly) variant.
+    ///
+    /// # Safety
+    /// Same as [`iter_unchecked`](QueryState::iter_unchecked).
+    pub unsafe fn get_unchecked<'w, D: QueryData<State = Q::State>>(
+        &self, world: WorldCell<'w>, entity: Entity,
+        last: ChangeMark, current: ChangeMark,
+    ) -> Result<D::Item<'w>, QueryEntityError> {
+        let entities = world.entities();
+        if !entities.contains(entity) {
+            return Err(QueryEntityError::Nonexistent)
+        }
+
+        let Some(location) = entities.location(entity) else { return Err(QueryEntityError::Mismatch) };
+        if !self.archetype_bits.contains(location.archetype_id.0) {
+            return Err(QueryEntityError::Mismatch)
+        }
+
+        let components = world.components();
+        let archetype = components.archetypes().get_unchecked(location.archetype_id.0);
+        let table = archetype.table_id().map(|id| components.tables().get_unchecked(id.0));
+        let table_index = location.table_index.unwrap_or_default();
+
+        let mut filter = F::init_fetch(world, &self.filter, last, current);
+        F::set_archetype(&mut filter, &self.filter, archetype, table);
+        if !F::filter_fetch(&mut filter, entity, table_index) {
+            return Err(QueryEntityError::Mismatch)
+        }
+
+        let mut data = D::init_fetch(world, &self.data, last, current);
+        D::set_archetype(&mut data, &self.data, archetype, table);
+        Ok(D::fetch(&mut data, entity, table_index))
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    component::{
+        Component, ComponentId, ComponentHooks,
+    },
+    entity::Entity,
+    relationship::{
+        RelationLookup, Ancestors,
+    },
+    world::{
+        World, DeferredWorld,
+    },
+};
+
+/// A component relating its entity, the source, to a target entity. The target holds a
+/// [`RelationshipTarget`] collection of its sources, kept up-to-date by the [hooks](relationship_hooks)
+/// of both components. Usually derived with `#[relationship(target = ...)]`.
+pub trait Relationship: Component {
+    /// The collection of sources held by the target entity.
+    type Target: RelationshipTarget<Relationship = Self>;
+
+    /// Returns the target entity.
+    fn get(&self) -> Entity;
+
+    /// Creates the relationship targeting `entity`. Relationships targeting their own source or
+    /// one of its ancestors are rejected by the [hooks](relationship_hooks) once inserted.
+    fn from_entity(entity: Entity) -> Self;
+}
+
+/// A component collecting the sources of a [`Relationship`] that target its entity. Usually
+/// derived with `#[relationship_target(relationship = ...)]`.
+pub trait RelationshipTarget: Component {
+    type Relationship: Relationship<Target = Self>;
+
+    /// Returns the source entities, in insertion order.
+    fn sources(&self) -> &[Entity];
+
+    /// Returns the source entities mutably. Only the relationship hooks should mutate it, as
+    /// otherwise both sides fall out of sync.
+    fn sources_mut(&mut self) -> &mut Vec<Entity>;
+
+    fn from_sources(sources: Vec<Entity>) -> Self;
+}
+
+/// Registers the hooks of the relationship `R`, adding the source to the target's collection
+/// when inserted, and taking it out when replaced or removed.
+///
+/// Relationships that would form a cycle, i.e. ones targeting the source itself or one of its
+/// [`Ancestors`], aren't added to the target's collection and are removed by the commands applied
+/// at the end of the insertion.
+///
+/// If the target doesn't hold the collection yet, it's inserted by those commands as well.
+#[inline]
+pub fn relationship_hooks<R: Relationship>(hooks: &mut ComponentHooks) {
+    hooks
+        .on_insert(on_insert::<R>)
+        .on_replace(on_detach::<R>)
+        .on_remove(on_detach::<R>);
+}
+
+/// Registers the hooks of the relationship target `T`, removing the [`Relationship`] of its
+/// sources by the commands applied at the end of its removal.
+#[inline]
+pub fn relationship_target_hooks<T: RelationshipTarget>(hooks: &mut ComponentHooks) {
+    hooks.on_remove(on_target_remove::<T>);
+}
+
+#[inline]
+fn target_of<R: Relationship>(world: &World, entity: Entity) -> Option<Entity> {
+    RelationLookup::<'_, R>::lookup(world, entity).map(R::get)
+}
+
+fn on_insert<R: Relationship>(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(target) = target_of::<R>(&world, entity) else { return };
+    if target == entity || Ancestors::<R>::new(&*world, target).any(|ancestor| ancestor == entity) {
+        world.commands().add(move |world: &mut World| {
+            if target_of::<R>(world, entity) == Some(target) {
+                world.view_mut(entity)?.remove::<R>();
+            }
+
+            Ok(())
+        });
+
+        return
+    }
+
+    if let Some(mut sources) = world.get_mut::<R::Target>(target) {
+        let sources = sources.sources_mut();
+        if !sources.contains(&entity) {
+            sources.push(entity);
+        }
+
+        return
+    }
+
+    world.commands().add(move |world: &mut World| {
+        // The relationship may have changed before the commands are flushed.
+        if target_of::<R>(world, entity) != Some(target) { return Ok(()) }
+        if let Ok(mut view) = world.view_mut(target) {
+            match view.get_mut::<R::Target>() {
+                Some(mut sources) => {
+                    let sources = sources.sources_mut();
+                    if !sources.contains(&entity) {
+                        sources.push(entity);
+                    }
+                },
+                None => view.insert(R::Target::from_sources(vec![entity])),
+            }
+        }
+
+        Ok(())
+    });
+}
+
+fn on_detach<R: Relationship>(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(target) = target_of::<R>(&world, entity) else { return };
+    if let Some(mut sources) = world.get_mut::<R::Target>(target) {
+        sources.sources_mut().retain(|&source| source != entity);
+    }
+}
+
+fn on_target_remove<T: RelationshipTarget>(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(sources) = RelationLookup::<'_, T>::lookup(&*world, entity).map(|sources| sources.sources().to_vec()) else { return };
+    if sources.is_empty() { return }
+
+    world.commands().add(move |world: &mut World| {
+        for source in sources {
+            if target_of::<T::Relationship>(world, source) == Some(entity) {
+                world.view_mut(source)?.remove::<T::Relationship>();
+            }
+        }
+
+        Ok(())
+    });
+}
@@ -0,0 +1,131 @@
+use fei_common::prelude::*;
+use crate::{
+    component::Component,
+    entity::Entity,
+    query::{
+        WorldQuery, QueryData, QueryFilter,
+        Query,
+    },
+    relationship::{
+        Relationship, RelationshipTarget,
+    },
+    world::World,
+};
+use std::marker::PhantomData;
+
+/// Looks up components of entities to walk relations with, either through the [`World`] or a
+/// [`Query`] fetching the components.
+pub trait RelationLookup<'a, C: Component>: Copy {
+    fn lookup(self, entity: Entity) -> Option<&'a C>;
+}
+
+impl<'a, C: Component> RelationLookup<'a, C> for &'a World {
+    #[inline]
+    fn lookup(self, entity: Entity) -> Option<&'a C> {
+        self.view(entity).ok()?.get::<C>().map(|component| component.into_inner())
+    }
+}
+
+impl<'a, 'w, 's, C: Component, Q: QueryData, F: QueryFilter> RelationLookup<'a, C> for &'a Query<'w, 's, Q, F>
+where
+    Q::ReadOnly: WorldQuery<Item<'a> = &'a C>,
+{
+    #[inline]
+    fn lookup(self, entity: Entity) -> Option<&'a C> {
+        self.get(entity).ok()
+    }
+}
+
+/// Iterates over the entities an entity is transitively related to through `R`, i.e. its target,
+/// the target's target, and so on. Each entity is visited at most once, even if the relations
+/// form a cycle.
+pub struct Ancestors<'a, R: Relationship, L: RelationLookup<'a, R> = &'a World> {
+    lookup: L,
+    next: Option<Entity>,
+    visited: FxHashSet<Entity>,
+    _marker: PhantomData<fn() -> &'a R>,
+}
+
+impl<'a, R: Relationship, L: RelationLookup<'a, R>> Ancestors<'a, R, L> {
+    #[inline]
+    pub fn new(lookup: L, entity: Entity) -> Self {
+        Self {
+            lookup,
+            next: lookup.lookup(entity).map(R::get),
+            visited: FxHashSet::from_iter([entity]),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, R: Relationship, L: RelationLookup<'a, R>> Iterator for Ancestors<'a, R, L> {
+    type Item = Entity;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let entity = self.next.take().filter(|&entity| self.visited.insert(entity))?;
+        self.next = self.lookup.lookup(entity).map(R::get);
+        Some(entity)
+    }
+}
+
+/// Iterates over the sources collected in `T` of an entity depth-first, visiting each source
+/// before its own sources. Each entity is visited at most once, even if the relations form a cycle.
+pub struct Descendants<'a, T: RelationshipTarget, L: RelationLookup<'a, T> = &'a World> {
+    lookup: L,
+    stack: Vec<Entity>,
+    visited: FxHashSet<Entity>,
+    _marker: PhantomData<fn() -> &'a T>,
+}
+
+impl<'a, T: RelationshipTarget, L: RelationLookup<'a, T>> Descendants<'a, T, L> {
+    #[inline]
+    pub fn new(lookup: L, entity: Entity) -> Self {
+        let mut stack = Vec::new();
+        if let Some(sources) = lookup.lookup(entity) {
+            stack.extend(sources.sources().iter().rev());
+        }
+
+        Self { lookup, stack, visited: FxHashSet::from_iter([entity]), _marker: PhantomData, }
+    }
+}
+
+impl<'a, T: RelationshipTarget, L: RelationLookup<'a, T>> Iterator for Descendants<'a, T, L> {
+    type Item = Entity;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let entity = loop {
+            let entity = self.stack.pop()?;
+            if self.visited.insert(entity) { break entity }
+        };
+
+        if let Some(sources) = self.lookup.lookup(entity) {
+            self.stack.extend(sources.sources().iter().rev());
+        }
+
+        Some(entity)
+    }
+}
+
+impl<'w, 's, Q: QueryData, F: QueryFilter> Query<'w, 's, Q, F> {
+    /// Iterates over the [`Ancestors`] of `entity` through `R`, stopping at the first one that
+    /// doesn't match the query.
+    #[inline]
+    pub fn iter_ancestors<'a, R: Relationship>(&'a self, entity: Entity) -> Ancestors<'a, R, &'a Self>
+    where
+        Q::ReadOnly: WorldQuery<Item<'a> = &'a R>,
+    {
+        Ancestors::new(self, entity)
+    }
+
+    /// Iterates over the [`Descendants`] of `entity` through `T`, skipping the sources of ones that
+    /// don't match the query.
+    #[inline]
+    pub fn iter_descendants<'a, T: RelationshipTarget>(&'a self, entity: Entity) -> Descendants<'a, T, &'a Self>
+    where
+        Q::ReadOnly: WorldQuery<Item<'a> = &'a T>,
+    {
+        Descendants::new(self, entity)
+    }
+}
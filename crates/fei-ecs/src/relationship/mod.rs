@@ -0,0 +1,113 @@
+mod def;
+mod iter;
+
+pub use def::*;
+pub use iter::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_common::prelude::*;
+    use fei_ecs_macros::Component;
+    use crate::{
+        entity::Entity,
+        query::Query,
+        system::{
+            IntoSystem, System, In,
+        },
+        world::World,
+    };
+
+    #[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+    #[relationship(target = LikedBy)]
+    struct Likes(Entity);
+    #[derive(Component, Debug, Default)]
+    #[relationship_target(relationship = Likes)]
+    struct LikedBy {
+        likers: Vec<Entity>,
+    }
+
+    #[inline]
+    fn liked_by(world: &World, entity: Entity) -> Vec<Entity> {
+        world.view(entity).ok()
+            .and_then(|view| view.get::<LikedBy>())
+            .map_or_else(Vec::new, |liked| liked.sources().to_vec())
+    }
+
+    #[test]
+    fn relationship() -> anyhow::Result<()> {
+        let mut world = World::default();
+        let fei = world.spawn_empty()?.id();
+        let a = world.spawn(Likes(fei))?.id();
+
+        // Targets without the collection receive it right away.
+        assert_eq!(liked_by(&world, fei), [a]);
+
+        // Afterwards, the collection is updated in-place.
+        let b = world.spawn(Likes(fei))?.id();
+        assert_eq!(liked_by(&world, fei), [a, b]);
+
+        world.view_mut(a)?.insert(Likes(b));
+        assert_eq!(liked_by(&world, fei), [b]);
+        assert_eq!(liked_by(&world, b), [a]);
+
+        world.view_mut(a)?.remove::<Likes>();
+        world.despawn(b)?;
+        assert!(liked_by(&world, fei).is_empty());
+        assert!(liked_by(&world, b).is_empty());
+
+        // Removing the collection removes the relationship from its sources.
+        let c = world.spawn(Likes(fei))?.id();
+        world.view_mut(fei)?.remove::<LikedBy>();
+        assert!(!world.view(c)?.contains::<Likes>());
+        Ok(())
+    }
+
+    #[test]
+    fn walk() -> anyhow::Result<()> {
+        fn ancestors(In(entity): In<Entity>, likes: Query<&Likes>) -> anyhow::Result<Vec<Entity>> {
+            Ok(likes.iter_ancestors(entity).collect())
+        }
+
+        fn descendants(In(entity): In<Entity>, liked: Query<&LikedBy>) -> anyhow::Result<Vec<Entity>> {
+            Ok(liked.iter_descendants(entity).collect())
+        }
+
+        let mut world = World::default();
+        let root = world.spawn_empty()?.id();
+        let a = world.spawn(Likes(root))?.id();
+        let b = world.spawn(Likes(a))?.id();
+        let c = world.spawn(Likes(root))?.id();
+
+        assert_eq!(Ancestors::<Likes>::new(&world, b).collect::<Vec<_>>(), [a, root]);
+        assert_eq!(Descendants::<LikedBy>::new(&world, root).collect::<Vec<_>>(), [a, b, c]);
+
+        let mut ancestors = ancestors.into_system(&mut world)?;
+        let mut descendants = descendants.into_system(&mut world)?;
+        assert_eq!(ancestors.call(b, &mut world)?, [a, root]);
+        assert_eq!(descendants.call(root, &mut world)?, [a, b, c]);
+        assert!(descendants.call(b, &mut world)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn cycles() -> anyhow::Result<()> {
+        let mut world = World::default();
+        let a = world.spawn_empty()?.id();
+        let b = world.spawn(Likes(a))?.id();
+
+        // Self-targeting and cyclic relationships are never collected, and are removed right away.
+        world.view_mut(a)?.insert(Likes(b));
+        let c = world.spawn_empty()?.id();
+        world.view_mut(c)?.insert(Likes(c));
+        assert_eq!(liked_by(&world, a), [b]);
+        assert!(liked_by(&world, b).is_empty() && liked_by(&world, c).is_empty());
+        assert!(!world.view(a)?.contains::<Likes>() && !world.view(c)?.contains::<Likes>());
+        assert_eq!(world.view(b)?.get::<Likes>().map(|likes| likes.get()), Some(a));
+
+        // Hand-written collections may still form cycles, which walks visit once.
+        world.view_mut(b)?.insert(LikedBy { likers: vec![a], });
+        assert_eq!(Descendants::<LikedBy>::new(&world, a).collect::<Vec<_>>(), [b]);
+        Ok(())
+    }
+}
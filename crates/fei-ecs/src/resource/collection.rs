@@ -30,6 +30,8 @@ pub struct Resources {
 
     ids: FxHashMap<TypeId, ResourceId>,
     local_ids: FxHashMap<TypeId, ResourceLocalId>,
+    names: Vec<&'static str>,
+    local_names: Vec<&'static str>,
 }
 
 pub struct ResourceData {
@@ -71,7 +73,10 @@ impl Resources {
     #[inline]
     pub fn register<T: Resource>(&mut self) -> ResourceId {
         let id = self.ids.len();
-        *self.ids.entry(TypeId::of::<T>()).or_insert(ResourceId(id))
+        *self.ids.entry(TypeId::of::<T>()).or_insert_with(|| {
+            self.names.push(std::any::type_name::<T>());
+            ResourceId(id)
+        })
     }
 
     #[inline]
@@ -79,6 +84,7 @@ impl Resources {
         let id = self.local_ids.len();
         *self.local_ids.entry(TypeId::of::<T>()).or_insert_with(|| {
             self.local_threads.push(MaybeUninit::uninit());
+            self.local_names.push(std::any::type_name::<T>());
             ResourceLocalId(id)
         })
     }
@@ -88,11 +94,23 @@ impl Resources {
         self.ids.get(&TypeId::of::<T>()).copied()
     }
 
+    /// Returns the name of a registered resource type, used in diagnostics.
+    #[inline]
+    pub fn get_name(&self, id: ResourceId) -> Option<&'static str> {
+        self.names.get(id.0).copied()
+    }
+
     #[inline]
     pub fn get_local_id<T: ResourceLocal>(&self) -> Option<ResourceLocalId> {
         self.local_ids.get(&TypeId::of::<T>()).copied()
     }
 
+    /// Returns the name of a registered local resource type, used in diagnostics.
+    #[inline]
+    pub fn get_local_name(&self, id: ResourceLocalId) -> Option<&'static str> {
+        self.local_names.get(id.0).copied()
+    }
+
     #[inline]
     pub unsafe fn insert(&mut self, id: ResourceId, resource: BoxErased<'static>, current: ChangeMark) -> Option<BoxErased<'static>> {
         let ResourceData { inner, .. } = self.containers.insert(id, ResourceData::new(resource, current))?;
@@ -6,6 +6,7 @@ use crate::{
     },
     system::{
         SystemParam, ReadOnlySystemParam,
+        Access,
     },
     world::{
         World, WorldCell,
@@ -58,8 +59,10 @@ impl<'world, T: Resource> SystemParam for Res<'world, T> {
     }
 
     #[inline]
-    fn construct_state(world: &mut World) -> anyhow::Result<Self::State> {
-        Ok(world.register_res::<T>())
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        let id = world.register_res::<T>();
+        access.add_resource_read(id);
+        Ok(id)
     }
 }
 
@@ -78,8 +81,45 @@ impl<'world, T: Resource> SystemParam for ResMut<'world, T> {
     }
 
     #[inline]
-    fn construct_state(world: &mut World) -> anyhow::Result<Self::State> {
-        Ok(world.register_res::<T>())
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        let id = world.register_res::<T>();
+        access.add_resource_write(id);
+        Ok(id)
+    }
+}
+
+/// Like [`Res`], but `None` instead of failing if the resource isn't present.
+unsafe impl<'world, T: Resource> ReadOnlySystemParam for Option<Res<'world, T>> {}
+impl<'world, T: Resource> SystemParam for Option<Res<'world, T>> {
+    type State = ResourceId;
+    type Item<'w, 's> = Option<Res<'w, T>>;
+    type ReadOnly = Self;
+
+    #[inline]
+    unsafe fn construct<'w, 's>(world: WorldCell<'w>, state: &'s mut Self::State, last: ChangeMark, _: ChangeMark) -> anyhow::Result<Self::Item<'w, 's>> {
+        Ok(world.res_by_id(*state, last).map(|res| Res(res.casted())))
+    }
+
+    #[inline]
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        Res::<T>::construct_state(world, access)
+    }
+}
+
+/// Like [`ResMut`], but `None` instead of failing if the resource isn't present.
+impl<'world, T: Resource> SystemParam for Option<ResMut<'world, T>> {
+    type State = ResourceId;
+    type Item<'w, 's> = Option<ResMut<'w, T>>;
+    type ReadOnly = Option<Res<'world, T>>;
+
+    #[inline]
+    unsafe fn construct<'w, 's>(world: WorldCell<'w>, state: &'s mut Self::State, last: ChangeMark, current: ChangeMark) -> anyhow::Result<Self::Item<'w, 's>> {
+        Ok(world.res_by_id_mut(*state, last, current).map(|res| ResMut(res.casted())))
+    }
+
+    #[inline]
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        ResMut::<T>::construct_state(world, access)
     }
 }
 
@@ -99,8 +139,10 @@ impl<'world, T: ResourceLocal> SystemParam for ResLocal<'world, T> {
     }
 
     #[inline]
-    fn construct_state(world: &mut World) -> anyhow::Result<Self::State> {
-        Ok(world.register_res_local::<T>())
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        let id = world.register_res_local::<T>();
+        access.add_resource_local_read(id);
+        Ok(id)
     }
 }
 
@@ -119,8 +161,10 @@ impl<'world, T: ResourceLocal> SystemParam for ResLocalMut<'world, T> {
     }
 
     #[inline]
-    fn construct_state(world: &mut World) -> anyhow::Result<Self::State> {
-        Ok(world.register_res_local::<T>())
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+        let id = world.register_res_local::<T>();
+        access.add_resource_local_write(id);
+        Ok(id)
     }
 }
 
@@ -0,0 +1,78 @@
+use fei_common::prelude::*;
+use crate::{
+    schedule::{
+        SystemLabel, SystemSet,
+    },
+    system::{
+        IntoSystem, BoxedSystem,
+    },
+    world::World,
+};
+
+type SystemInit = Box<dyn FnOnce(&mut World) -> anyhow::Result<BoxedSystem> + Send + Sync>;
+
+/// A not-yet-initialized system along with its labels and ordering constraints, to be added to a
+/// [`Schedule`](crate::schedule::Schedule).
+pub struct SystemConfig {
+    pub(crate) init: SystemInit,
+    pub(crate) labels: Vec<Box<dyn SystemLabel>>,
+    pub(crate) before: Vec<Box<dyn SystemLabel>>,
+    pub(crate) after: Vec<Box<dyn SystemLabel>>,
+}
+
+pub trait IntoSystemConfig<Marker>: Sized {
+    fn into_config(self) -> SystemConfig;
+
+    /// Labels the system, so other systems may be ordered against it.
+    #[inline]
+    fn label(self, label: impl SystemLabel) -> SystemConfig {
+        let mut config = self.into_config();
+        config.labels.push(Box::new(label));
+        config
+    }
+
+    /// Adds the system to `set`, sharing the set's ordering constraints and conditions configured
+    /// with [`Schedule::configure_set`](crate::schedule::Schedule::configure_set). Systems may be in
+    /// several sets.
+    #[inline]
+    fn in_set(self, set: impl SystemSet) -> SystemConfig {
+        let mut config = self.into_config();
+        config.labels.push(Box::new(set));
+        config
+    }
+
+    /// Orders the system to run before every system labeled with `label`.
+    #[inline]
+    fn before(self, label: impl SystemLabel) -> SystemConfig {
+        let mut config = self.into_config();
+        config.before.push(Box::new(label));
+        config
+    }
+
+    /// Orders the system to run after every system labeled with `label`.
+    #[inline]
+    fn after(self, label: impl SystemLabel) -> SystemConfig {
+        let mut config = self.into_config();
+        config.after.push(Box::new(label));
+        config
+    }
+}
+
+impl IntoSystemConfig<()> for SystemConfig {
+    #[inline]
+    fn into_config(self) -> SystemConfig {
+        self
+    }
+}
+
+impl<Marker, Sys: IntoSystem<Marker, In = (), Out = ()> + 'static + Send + Sync> IntoSystemConfig<(Marker,)> for Sys {
+    #[inline]
+    fn into_config(self) -> SystemConfig {
+        SystemConfig {
+            init: Box::new(|world| Ok(Box::new(self.into_system(world)?))),
+            labels: Vec::new(),
+            before: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use fei_common::prelude::*;
+use crate::{
+    schedule::{
+        IntoSystemConfig, SystemConfig,
+        IntoSystemSetConfig, SystemSetConfig, SetConditionSystem,
+        SystemLabel,
+        ExecutorKind, run_multi_threaded,
+    },
+    system::{
+        Access, BoxedSystem,
+    },
+    world::World,
+};
+use anyhow::Context;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    mem,
+};
+
+#[derive(Error, Debug)]
+pub enum ScheduleError {
+    #[error("systems are ordered in a cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<&'static str>),
+    #[error("system `{}` is ordered against label `{:?}`, which no system has", .system, .label)]
+    UnknownLabel {
+        system: &'static str,
+        label: Box<dyn SystemLabel>,
+    },
+}
+
+/// A collection of systems, run in an order satisfying their [`before`](IntoSystemConfig::before)
+/// and [`after`](IntoSystemConfig::after) constraints. Systems with no constraints between them run
+/// in the order they're added, or at the same time if the [executor](ExecutorKind) is
+/// multithreaded and their [accesses](Access) don't conflict. Constraints and conditions may also be
+/// shared by every system in a [set](crate::schedule::SystemSet), see [`Schedule::configure_set`].
+#[derive(Default)]
+pub struct Schedule {
+    executor: ExecutorKind,
+    pending: Vec<SystemConfig>,
+    /// Systems initialized from `pending` that aren't in the run order yet, as sorting it failed.
+    staged: Vec<(BoxedSystem, SystemMeta)>,
+    sets: Vec<SystemSetConfig>,
+    /// Whether the run order must be re-sorted, e.g. after a set is configured.
+    dirty: bool,
+    systems: Vec<BoxedSystem>,
+    metas: Vec<SystemMeta>,
+    order: Vec<usize>,
+}
+
+/// Everything the executors need to know about a system, kept apart from the system itself so it
+/// may be read while the system is running.
+pub(crate) struct SystemMeta {
+    pub(crate) name: &'static str,
+    pub(crate) access: Access,
+    pub(crate) labels: Vec<Box<dyn SystemLabel>>,
+    pub(crate) before: Vec<Box<dyn SystemLabel>>,
+    pub(crate) after: Vec<Box<dyn SystemLabel>>,
+    /// Systems that must run after this one.
+    pub(crate) dependents: Vec<usize>,
+    /// The number of systems that must run before this one.
+    pub(crate) dependencies: usize,
+}
+
+impl Schedule {
+    #[inline]
+    pub fn executor(&self) -> ExecutorKind {
+        self.executor
+    }
+
+    #[inline]
+    pub fn set_executor(&mut self, executor: ExecutorKind) -> &mut Self {
+        self.executor = executor;
+        self
+    }
+
+    /// Adds a system to the schedule. It's only initialized on the next [`run`](Schedule::run) or
+    /// [`initialize`](Schedule::initialize).
+    #[inline]
+    pub fn add_system<Marker>(&mut self, system: impl IntoSystemConfig<Marker>) -> &mut Self {
+        self.pending.push(system.into_config());
+        self
+    }
+
+    /// Configures the ordering constraints and conditions of every system in a [set](crate::schedule::SystemSet).
+    /// Configuring the same set again adds to its previous configuration, including the conditions of
+    /// systems in the set that are already initialized.
+    #[inline]
+    pub fn configure_set(&mut self, set: impl IntoSystemSetConfig) -> &mut Self {
+        let config = set.into_set_config();
+        match self.sets.iter_mut().find(|other| *other.set == *config.set) {
+            Some(other) => other.merge(config),
+            None => self.sets.push(config),
+        }
+
+        self.dirty = true;
+        self
+    }
+
+    /// Returns the number of systems in the schedule, including uninitialized ones.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pending.len() + self.staged.len() + self.systems.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Initializes newly added systems and re-sorts the run order if there are any, or if a set has
+    /// been configured since.
+    ///
+    /// On failure, the previous run order is kept. Systems that failed to initialize are dropped,
+    /// while the others are kept to be sorted again on the next call.
+    pub fn initialize(&mut self, world: &mut World) -> anyhow::Result<()> {
+        if self.pending.is_empty() && self.staged.is_empty() && !self.dirty { return Ok(()) }
+        if self.dirty {
+            self.apply_set_conditions(world)?;
+        }
+
+        let mut pending = mem::take(&mut self.pending).into_iter();
+        while let Some(config) = pending.next() {
+            match self.initialize_system(world, config) {
+                Ok(staged) => self.staged.push(staged),
+                Err(error) => {
+                    self.pending.extend(pending);
+                    return Err(error)
+                },
+            }
+        }
+
+        // Sort the candidates in place, moving them back to the staging area if that fails.
+        let len = self.systems.len();
+        for (system, meta) in self.staged.drain(..) {
+            self.systems.push(system);
+            self.metas.push(meta);
+        }
+
+        match self.sort() {
+            Ok(order) => {
+                self.order = order;
+                self.dirty = false;
+                Ok(())
+            },
+            Err(error) => {
+                self.staged.extend(self.systems.drain(len..).zip(self.metas.drain(len..)));
+                Err(error.into())
+            },
+        }
+    }
+
+    /// Gives conditions added to sets since the last initialization to the already initialized
+    /// systems in those sets. Nothing changes if creating any of the conditions fails.
+    fn apply_set_conditions(&mut self, world: &mut World) -> anyhow::Result<()> {
+        if self.sets.iter().all(|set| set.applied == set.conditions.len()) { return Ok(()) }
+
+        let metas = self.metas.iter().chain(self.staged.iter().map(|(_, meta)| meta));
+        let mut conditions = metas
+            .map(|meta| SetConditionSystem::unapplied_conditions(world, &meta.labels, &self.sets))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let staged_conditions = conditions.split_off(self.metas.len());
+        self.systems = mem::take(&mut self.systems)
+            .into_iter().zip(conditions)
+            .map(|(system, conditions)| SetConditionSystem::wrap(system, conditions))
+            .collect();
+
+        self.staged = mem::take(&mut self.staged)
+            .into_iter().zip(staged_conditions)
+            .map(|((system, mut meta), conditions)| {
+                let system = SetConditionSystem::wrap(system, conditions);
+                meta.access = system.access().clone();
+                (system, meta)
+            })
+            .collect();
+
+        for (system, meta) in self.systems.iter().zip(&mut self.metas) {
+            meta.access = system.access().clone();
+        }
+
+        for set in &mut self.sets {
+            set.applied = set.conditions.len();
+        }
+
+        Ok(())
+    }
+
+    fn initialize_system(&self, world: &mut World, config: SystemConfig) -> anyhow::Result<(BoxedSystem, SystemMeta)> {
+        let system = (config.init)(world)?;
+        let conditions = SetConditionSystem::conditions(world, &config.labels, &self.sets)?;
+        let system = SetConditionSystem::wrap(system, conditions);
+
+        let meta = SystemMeta {
+            name: system.name(),
+            access: system.access().clone(),
+            labels: config.labels,
+            before: config.before,
+            after: config.after,
+            dependents: Vec::new(),
+            dependencies: 0,
+        };
+
+        Ok((system, meta))
+    }
+
+    /// Runs every system, then [syncs](World::sync_change_mark) the world's change mark. Stops
+    /// running systems once one fails, returning its error.
+    ///
+    /// Single-threaded executors [apply](crate::system::System::apply) each system's deferred state
+    /// right after it runs, while multithreaded ones apply all of them at the end, in run order, or
+    /// before [exclusive systems](crate::system::ExclusiveSystemFn) run.
+    pub fn run(&mut self, world: &mut World) -> anyhow::Result<()> {
+        self.initialize(world)?;
+
+        let result = match self.executor {
+            ExecutorKind::SingleThreaded => self.order.iter().try_for_each(|&index| {
+                self.systems[index]
+                    .call((), world)
+                    .with_context(|| format!("system `{}` failed", self.metas[index].name))
+            }),
+            ExecutorKind::MultiThreaded => run_multi_threaded(&mut self.systems, &self.metas, &self.order, world),
+        };
+
+        // Surface errors of hook commands applied while the systems ran.
+        let result = result.and_then(|()| world.flush_commands());
+        world.sync_change_mark();
+        result
+    }
+
+    /// Returns the names of the initialized systems, in run order.
+    #[inline]
+    pub fn system_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.order.iter().map(|&index| self.metas[index].name)
+    }
+
+    /// Sorts the run order, only updating the systems' dependencies if it succeeds.
+    fn sort(&mut self) -> Result<Vec<usize>, ScheduleError> {
+        let len = self.metas.len();
+
+        let mut labeled = FxHashMap::<&dyn SystemLabel, Vec<usize>>::default();
+        for (index, meta) in self.metas.iter().enumerate() {
+            for label in &meta.labels {
+                labeled.entry(&**label).or_default().push(index);
+            }
+        }
+
+        // `edges[a]` contains `b` if `a` must run before `b`.
+        let mut edges = vec![Vec::new(); len];
+        for (index, meta) in self.metas.iter().enumerate() {
+            let sets = self.sets.iter().filter(|set| meta.labels.contains(&set.set));
+            let labeled_with = |label: &dyn SystemLabel| labeled.get(label).ok_or_else(|| ScheduleError::UnknownLabel {
+                system: meta.name,
+                label: label.dyn_clone(),
+            });
+
+            for label in meta.before.iter().chain(sets.clone().flat_map(|set| &set.before)) {
+                edges[index].extend(labeled_with(&**label)?);
+            }
+
+            for label in meta.after.iter().chain(sets.flat_map(|set| &set.after)) {
+                for &other in labeled_with(&**label)? {
+                    edges[other].push(index);
+                }
+            }
+        }
+
+        drop(labeled);
+        for nexts in &mut edges {
+            nexts.sort_unstable();
+            nexts.dedup();
+        }
+
+        let mut in_degrees = vec![0usize; len];
+        for &next in edges.iter().flatten() {
+            in_degrees[next] += 1;
+        }
+
+        let dependencies = in_degrees.clone();
+
+        // Always pick the earliest added system that is ready, to keep the order deterministic.
+        let mut ready = (0..len).filter(|&index| in_degrees[index] == 0).map(Reverse).collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(len);
+        while let Some(Reverse(index)) = ready.pop() {
+            order.push(index);
+            for &next in &edges[index] {
+                in_degrees[next] -= 1;
+                if in_degrees[next] == 0 {
+                    ready.push(Reverse(next));
+                }
+            }
+        }
+
+        if order.len() != len {
+            return Err(ScheduleError::Cycle(self.find_cycle(&edges, &in_degrees)))
+        }
+
+        for ((meta, nexts), dependencies) in self.metas.iter_mut().zip(edges).zip(dependencies) {
+            meta.dependents = nexts;
+            meta.dependencies = dependencies;
+        }
+
+        Ok(order)
+    }
+
+    fn find_cycle(&self, edges: &[Vec<usize>], in_degrees: &[usize]) -> Vec<&'static str> {
+        // Every unsorted system is either in a cycle or after one, so walking backwards through
+        // unsorted predecessors must eventually revisit a system.
+        let mut predecessors = vec![Vec::new(); edges.len()];
+        for (index, nexts) in edges.iter().enumerate() {
+            for &next in nexts {
+                predecessors[next].push(index);
+            }
+        }
+
+        let mut visited = vec![None; edges.len()];
+        let mut path = Vec::new();
+        let mut current = (0..edges.len()).find(|&index| in_degrees[index] > 0).unwrap_or_default();
+
+        while visited[current].is_none() {
+            visited[current] = Some(path.len());
+            path.push(current);
+            current = predecessors[current]
+                .iter()
+                .copied()
+                .find(|&prev| in_degrees[prev] > 0)
+                .unwrap_or(current);
+        }
+
+        let start = visited[current].unwrap_or_default();
+        let mut cycle = path[start..].iter().rev().map(|&index| self.metas[index].name).collect::<Vec<_>>();
+        cycle.push(cycle[0]);
+        cycle
+    }
+}
@@ -0,0 +1,200 @@
+use fei_common::prelude::*;
+use crate::{
+    schedule::SystemMeta,
+    system::{
+        Access, BoxedSystem,
+    },
+    world::{
+        World, WorldCell,
+    },
+};
+use anyhow::Context;
+use fixedbitset::FixedBitSet;
+use std::{
+    panic::{
+        self,
+        AssertUnwindSafe,
+    },
+    sync::mpsc,
+    thread,
+};
+
+/// How a [`Schedule`](crate::schedule::Schedule) runs its systems.
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ExecutorKind {
+    /// Runs systems one by one on the calling thread.
+    #[default]
+    SingleThreaded,
+    /// Runs systems whose [accesses](Access) don't conflict at the same time on the global
+    /// [`rayon`] thread pool. Systems accessing [local resources](crate::resource::ResourceLocal)
+    /// always run on the calling thread, which must not be a thread of the pool itself.
+    ///
+    /// [Exclusive systems](crate::system::ExclusiveSystemFn) are barriers: once one is ready, no
+    /// other system starts until it has run on the calling thread, after every running system has
+    /// finished and the deferred state of every finished system has been applied.
+    MultiThreaded,
+}
+
+#[derive(Copy, Clone)]
+struct SystemsPtr(*mut BoxedSystem);
+
+// Safety: Each system is only ever handed to one thread at a time.
+unsafe impl Send for SystemsPtr {}
+unsafe impl Sync for SystemsPtr {}
+
+struct ExecutorState<'a> {
+    metas: &'a [SystemMeta],
+    /// The number of systems left to run before each system.
+    dependencies: Vec<usize>,
+    /// Systems whose dependencies have all run, sorted by index.
+    ready: Vec<usize>,
+    /// Systems currently running on the pool, and the union of their accesses.
+    running: Vec<usize>,
+    active: Access,
+    finished: usize,
+    /// Systems that have run, and ones whose deferred state has been applied.
+    done: FixedBitSet,
+    applied: FixedBitSet,
+    result: anyhow::Result<()>,
+}
+
+impl<'a> ExecutorState<'a> {
+    #[inline]
+    fn new(metas: &'a [SystemMeta]) -> Self {
+        let dependencies = metas.iter().map(|meta| meta.dependencies).collect::<Vec<_>>();
+        Self {
+            metas,
+            ready: (0..metas.len()).filter(|&index| dependencies[index] == 0).collect(),
+            dependencies,
+            running: Vec::new(),
+            active: default(),
+            finished: 0,
+            done: FixedBitSet::with_capacity(metas.len()),
+            applied: FixedBitSet::with_capacity(metas.len()),
+            result: Ok(()),
+        }
+    }
+
+    /// Removes and returns the first ready system compatible with the running ones. Ready exclusive
+    /// systems are returned first, but only once nothing is running.
+    #[inline]
+    fn next_ready(&mut self) -> Option<usize> {
+        if self.result.is_err() { return None }
+
+        let position = match self.ready.iter().position(|&index| self.metas[index].access.is_exclusive()) {
+            Some(..) if !self.running.is_empty() => return None,
+            Some(position) => position,
+            None => self.ready.iter().position(|&index| self.metas[index].access.is_compatible(&self.active))?,
+        };
+
+        Some(self.ready.remove(position))
+    }
+
+    /// Applies the deferred state of every system that has run but hasn't been applied yet, in `order`.
+    ///
+    /// # Safety
+    /// No system may be running.
+    unsafe fn apply(&mut self, systems: SystemsPtr, order: &[usize], world: &mut World) {
+        for &index in order {
+            if !self.done.contains(index) || self.applied.put(index) { continue }
+
+            let result = (*systems.0.add(index))
+                .apply(world)
+                .with_context(|| format!("system `{}` failed to apply", self.metas[index].name));
+
+            if self.result.is_ok() {
+                self.result = result;
+            }
+        }
+    }
+
+    #[inline]
+    fn start(&mut self, index: usize) {
+        self.running.push(index);
+        self.active.extend(&self.metas[index].access);
+    }
+
+    fn finish(&mut self, index: usize, result: anyhow::Result<()>) {
+        if let Some(position) = self.running.iter().position(|&running| running == index) {
+            self.running.swap_remove(position);
+            self.active.clear();
+            for &running in &self.running {
+                self.active.extend(&self.metas[running].access);
+            }
+        }
+
+        self.finished += 1;
+        self.done.insert(index);
+        if let Err(error) = result {
+            if self.result.is_ok() {
+                self.result = Err(error.context(format!("system `{}` failed", self.metas[index].name)));
+            }
+        }
+
+        for &next in &self.metas[index].dependents {
+            self.dependencies[next] -= 1;
+            if self.dependencies[next] == 0 {
+                let position = self.ready.partition_point(|&ready| ready < next);
+                self.ready.insert(position, next);
+            }
+        }
+    }
+}
+
+/// Runs the systems on the global thread pool, starting each one as soon as its dependencies have
+/// finished and no running system conflicts with it, then applies their deferred state in `order`.
+pub(crate) fn run_multi_threaded(systems: &mut [BoxedSystem], metas: &[SystemMeta], order: &[usize], world: &mut World) -> anyhow::Result<()> {
+    let mut state = ExecutorState::new(metas);
+    {
+        let systems = SystemsPtr(systems.as_mut_ptr());
+        let world = unsafe { WorldCell::write(world) };
+        let (sender, receiver) = mpsc::channel::<(usize, thread::Result<anyhow::Result<()>>)>();
+
+        // Safety:
+        // - `ExecutorState` never starts a system twice, nor while it's running, so no system is
+        //   aliased.
+        // - Running systems' accesses are compatible with each other, so they don't alias any
+        //   component or resource either.
+        // - Exclusive systems only start once nothing is running, and block others from starting.
+        rayon::in_place_scope(|scope| loop {
+            while let Some(index) = state.next_ready() {
+                if metas[index].access.is_exclusive() {
+                    unsafe { state.apply(systems, order, world.get_mut()) };
+                    if state.result.is_err() { break }
+
+                    let result = unsafe { (*systems.0.add(index)).call_unchecked((), world) };
+                    state.finish(index, result);
+                } else if metas[index].access.is_local() {
+                    // Local resources may only be accessed from this thread.
+                    let result = unsafe { (*systems.0.add(index)).call_unchecked((), world) };
+                    state.finish(index, result);
+                } else {
+                    state.start(index);
+                    let sender = sender.clone();
+                    scope.spawn(move |_| {
+                        let systems = systems;
+                        // Panics are forwarded instead, so the calling thread doesn't wait forever.
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+                            (*systems.0.add(index)).call_unchecked((), world)
+                        }));
+
+                        // The receiver outlives the scope, so this never fails.
+                        let _ = sender.send((index, result));
+                    });
+                }
+            }
+
+            if state.running.is_empty() { break }
+            match receiver.recv() {
+                Ok((index, Ok(result))) => state.finish(index, result),
+                Ok((.., Err(payload))) => panic::resume_unwind(payload),
+                Err(..) => break,
+            }
+        });
+    }
+
+    // Safety: Every started system has finished.
+    unsafe { state.apply(SystemsPtr(systems.as_mut_ptr()), order, world) };
+    debug_assert!(state.result.is_err() || state.finished == systems.len());
+    state.result
+}
@@ -0,0 +1,67 @@
+use std::{
+    any::Any,
+    fmt::Debug,
+    hash::{
+        Hash, Hasher,
+    },
+};
+
+/// A label identifying one or more systems in a [`Schedule`](crate::schedule::Schedule), used to
+/// order them with [`before`](crate::schedule::IntoSystemConfig::before) and
+/// [`after`](crate::schedule::IntoSystemConfig::after). Implemented for every cloneable, hashable,
+/// and comparable type, e.g. `&'static str` or a field-less enum.
+pub trait SystemLabel: 'static + Send + Sync + Debug {
+    fn dyn_clone(&self) -> Box<dyn SystemLabel>;
+
+    fn dyn_eq(&self, other: &dyn SystemLabel) -> bool;
+
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: 'static + Send + Sync + Debug + Clone + Eq + Hash> SystemLabel for T {
+    #[inline]
+    fn dyn_clone(&self) -> Box<dyn SystemLabel> {
+        Box::new(self.clone())
+    }
+
+    #[inline]
+    fn dyn_eq(&self, other: &dyn SystemLabel) -> bool {
+        other.as_any().downcast_ref::<T>().is_some_and(|other| self == other)
+    }
+
+    #[inline]
+    fn dyn_hash(&self, mut state: &mut dyn Hasher) {
+        self.type_id().hash(&mut state);
+        self.hash(&mut state);
+    }
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl PartialEq for dyn SystemLabel {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn SystemLabel {}
+
+impl Hash for dyn SystemLabel {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dyn_hash(state);
+    }
+}
+
+impl Clone for Box<dyn SystemLabel> {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.dyn_clone()
+    }
+}
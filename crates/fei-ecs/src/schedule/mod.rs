@@ -0,0 +1,306 @@
+mod config;
+mod def;
+mod executor;
+mod label;
+mod set;
+
+pub use config::*;
+pub use def::*;
+pub use executor::*;
+pub use label::*;
+pub use set::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_common::prelude::*;
+    use fei_ecs_macros::{
+        Component,
+        Resource, ResourceLocal,
+        SystemSet,
+    };
+    use crate::{
+        command::Commands,
+        resource::{
+            Res, ResMut, ResLocalMut,
+        },
+        system::resource_exists,
+        world::World,
+    };
+    use std::{
+        sync::atomic::{
+            AtomicUsize, Ordering,
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<&'static str>);
+
+    fn a(mut log: ResMut<Log>) -> anyhow::Result<()> {
+        log.0.push("a");
+        Ok(())
+    }
+
+    fn b(mut log: ResMut<Log>) -> anyhow::Result<()> {
+        log.0.push("b");
+        Ok(())
+    }
+
+    fn c(mut log: ResMut<Log>) -> anyhow::Result<()> {
+        log.0.push("c");
+        Ok(())
+    }
+
+    #[test]
+    fn ordering() -> anyhow::Result<()> {
+        let mut world = World::default();
+        world.init_res::<Log>();
+
+        let mut schedule = Schedule::default();
+        schedule
+            .add_system(c.label("c").after("b"))
+            .add_system(a.label("a"))
+            .add_system(b.label("b").after("a"));
+
+        schedule.run(&mut world)?;
+        world.res_mut::<Log>().unwrap().0.push("|");
+
+        // Change marks are synced at the end of each run.
+        let last = world.last_change_mark();
+        schedule.run(&mut world)?;
+        assert!(world.last_change_mark().newer_than(last));
+        assert_eq!(world.res::<Log>().unwrap().0, ["a", "b", "c", "|", "a", "b", "c"]);
+        assert_eq!(schedule.system_names().count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> anyhow::Result<()> {
+        fn fail() -> anyhow::Result<()> {
+            anyhow::bail!("failed")
+        }
+
+        let mut world = World::default();
+        world.init_res::<Log>();
+
+        let mut cycle = Schedule::default();
+        cycle
+            .add_system(a.label("a").after("c"))
+            .add_system(b.label("b").after("a"))
+            .add_system(c.label("c").after("b"));
+
+        let error = cycle.run(&mut world).unwrap_err();
+        let Some(ScheduleError::Cycle(systems)) = error.downcast_ref::<ScheduleError>() else { panic!("{error}") };
+        assert_eq!(systems.len(), 4);
+
+        let mut unknown = Schedule::default();
+        unknown.add_system(a.after("nothing"));
+        assert!(matches!(unknown.run(&mut world).unwrap_err().downcast_ref(), Some(ScheduleError::UnknownLabel { .. })));
+
+        let mut failing = Schedule::default();
+        failing.add_system(fail);
+        let error = failing.run(&mut world).unwrap_err();
+        assert!(error.to_string().contains("fail"));
+        assert_eq!(error.root_cause().to_string(), "failed");
+
+        Ok(())
+    }
+
+    #[test]
+    fn recovery() -> anyhow::Result<()> {
+        fn conflicting(_: ResMut<Log>, _: Res<Log>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        let mut world = World::default();
+        world.init_res::<Log>();
+
+        let mut schedule = Schedule::default();
+        schedule.add_system(a.label("a"));
+        schedule.run(&mut world)?;
+
+        // A failed sort keeps the previous run order, and sorts again once fixed.
+        schedule.add_system(c.after("b"));
+        assert!(schedule.run(&mut world).is_err());
+        assert_eq!(schedule.system_names().count(), 1);
+        assert_eq!(schedule.len(), 2);
+
+        // Systems failing to initialize are dropped, but not the ones added after them.
+        schedule
+            .add_system(conflicting)
+            .add_system(b.label("b").after("a"));
+        assert!(schedule.run(&mut world).is_err());
+        assert_eq!(schedule.len(), 3);
+
+        world.res_mut::<Log>().unwrap().0.clear();
+        schedule.run(&mut world)?;
+        assert_eq!(world.res::<Log>().unwrap().0, ["a", "b", "c"]);
+        Ok(())
+    }
+
+    #[test]
+    fn multi_threaded() -> anyhow::Result<()> {
+        #[derive(Resource, Default)]
+        struct Overlap {
+            current: AtomicUsize,
+            max: AtomicUsize,
+        }
+
+        impl Overlap {
+            fn enter(&self) {
+                let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max.fetch_max(current, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+                self.current.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        #[derive(ResourceLocal, Default)]
+        struct Frames(u32);
+        #[derive(Component)]
+        struct Spawned;
+
+        fn read(_: Res<Log>, overlap: Res<Overlap>) -> anyhow::Result<()> {
+            overlap.enter();
+            Ok(())
+        }
+
+        fn write(mut log: ResMut<Log>, overlap: Res<Overlap>) -> anyhow::Result<()> {
+            overlap.enter();
+            log.0.push("write");
+            Ok(())
+        }
+
+        fn local(mut frames: ResLocalMut<Frames>) -> anyhow::Result<()> {
+            frames.0 += 1;
+            Ok(())
+        }
+
+        fn spawn(mut commands: Commands) -> anyhow::Result<()> {
+            commands.spawn(Spawned)?;
+            Ok(())
+        }
+
+        let mut world = World::default();
+        world.init_res::<Log>();
+        world.init_res::<Overlap>();
+        world.init_res_local::<Frames>()?;
+
+        let mut schedule = Schedule::default();
+        schedule
+            .set_executor(ExecutorKind::MultiThreaded)
+            .add_system(read)
+            .add_system(read)
+            .add_system(write)
+            .add_system(local)
+            .add_system(spawn.label("spawn"))
+            .add_system(spawn.after("spawn").label("spawn again"));
+
+        schedule.run(&mut world)?;
+        schedule.run(&mut world)?;
+
+        // `write` conflicts with both `read`s, so at most the two of them may overlap.
+        let max = world.res::<Overlap>().unwrap().max.load(Ordering::SeqCst);
+        assert!(max <= 2);
+        if rayon::current_num_threads() > 1 {
+            assert_eq!(max, 2);
+        }
+
+        assert_eq!(world.res::<Log>().unwrap().0, ["write", "write"]);
+        assert_eq!(world.res_local::<Frames>()?.unwrap().0, 2);
+        assert_eq!(world.query::<&Spawned>().iter(&world).count(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn exclusive() -> anyhow::Result<()> {
+        #[derive(Resource, Default)]
+        struct Running(AtomicUsize);
+        #[derive(Component)]
+        struct Spawned;
+
+        fn busy(running: Res<Running>) -> anyhow::Result<()> {
+            running.0.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            running.0.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn spawn(mut commands: Commands) -> anyhow::Result<()> {
+            commands.spawn(Spawned)?;
+            Ok(())
+        }
+
+        // Runs alone, after the commands of preceding systems have been applied.
+        fn barrier(world: &mut World) -> anyhow::Result<()> {
+            let running = world.res::<Running>().unwrap().0.load(Ordering::SeqCst);
+            let spawned = world.query::<&Spawned>().iter(world).count();
+            world.res_mut::<Log>().unwrap().0.push(if running == 0 && spawned == 1 { "barrier" } else { "overlap" });
+            Ok(())
+        }
+
+        let mut world = World::default();
+        world.init_res::<Log>();
+        world.init_res::<Running>();
+
+        let mut schedule = Schedule::default();
+        schedule
+            .set_executor(ExecutorKind::MultiThreaded)
+            .add_system(busy)
+            .add_system(busy)
+            .add_system(spawn.label("spawn"))
+            .add_system(barrier.label("barrier").after("spawn"))
+            .add_system(busy.after("barrier"));
+
+        schedule.run(&mut world)?;
+        assert_eq!(world.res::<Log>().unwrap().0, ["barrier"]);
+        assert_eq!(world.query::<&Spawned>().iter(&world).count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn sets() -> anyhow::Result<()> {
+        #[derive(SystemSet, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+        enum Stage {
+            First,
+            Second,
+        }
+
+        #[derive(SystemSet, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+        struct Gate;
+        #[derive(Resource)]
+        struct Open;
+
+        let mut world = World::default();
+        world.init_res::<Log>();
+
+        let mut schedule = Schedule::default();
+        schedule
+            .configure_set(Stage::Second.after(Stage::First))
+            .configure_set(Gate.run_if(resource_exists::<Open>))
+            .add_system(c.in_set(Stage::Second).in_set(Gate))
+            .add_system(b.in_set(Stage::Second))
+            .add_system(a.in_set(Stage::First));
+
+        schedule.run(&mut world)?;
+        world.res_mut::<Log>().unwrap().0.push("|");
+        world.insert_res(Open);
+        schedule.run(&mut world)?;
+        assert_eq!(world.res::<Log>().unwrap().0, ["a", "b", "|", "a", "c", "b"]);
+
+        // Conditions added afterwards also apply to already initialized systems.
+        world.res_mut::<Log>().unwrap().0.clear();
+        world.remove_res::<Open>();
+        schedule.configure_set(Stage::First.run_if(resource_exists::<Open>)).run(&mut world)?;
+        world.insert_res(Open);
+        schedule.run(&mut world)?;
+        assert_eq!(world.res::<Log>().unwrap().0, ["b", "a", "c", "b"]);
+
+        // Configuring sets re-sorts already initialized systems.
+        let error = schedule.configure_set(Stage::First.after(Stage::Second)).run(&mut world).unwrap_err();
+        assert!(error.to_string().contains("cycle"));
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use fei_common::prelude::*;
+use crate::{
+    schedule::SystemLabel,
+    system::{
+        System, BoxedSystem, BoxedCondition, Condition,
+        Access,
+    },
+    world::{
+        World, WorldCell,
+    },
+};
+use anyhow::Context;
+
+type ConditionInit = Box<dyn Fn(&mut World) -> anyhow::Result<BoxedCondition> + Send + Sync>;
+
+/// A [`SystemLabel`] grouping systems that join it with [`in_set`](crate::schedule::IntoSystemConfig::in_set),
+/// so their ordering and [conditions](Condition) may be configured once with
+/// [`Schedule::configure_set`](crate::schedule::Schedule::configure_set). Usually derived for
+/// field-less enums, along with `Debug`, `Clone`, `Eq`, and `Hash`.
+pub trait SystemSet: SystemLabel {}
+
+/// Ordering constraints and run conditions shared by every system in a [`SystemSet`].
+pub struct SystemSetConfig {
+    pub(crate) set: Box<dyn SystemLabel>,
+    pub(crate) before: Vec<Box<dyn SystemLabel>>,
+    pub(crate) after: Vec<Box<dyn SystemLabel>>,
+    pub(crate) conditions: Vec<ConditionInit>,
+    /// The number of conditions already given to initialized systems in the set.
+    pub(crate) applied: usize,
+}
+
+impl SystemSetConfig {
+    /// Merges the constraints and conditions of another configuration of the same set.
+    #[inline]
+    pub(crate) fn merge(&mut self, other: SystemSetConfig) {
+        self.before.extend(other.before);
+        self.after.extend(other.after);
+        self.conditions.extend(other.conditions);
+    }
+}
+
+pub trait IntoSystemSetConfig: Sized {
+    fn into_set_config(self) -> SystemSetConfig;
+
+    /// Orders every system in the set to run before every system labeled with `label`.
+    #[inline]
+    fn before(self, label: impl SystemLabel) -> SystemSetConfig {
+        let mut config = self.into_set_config();
+        config.before.push(Box::new(label));
+        config
+    }
+
+    /// Orders every system in the set to run after every system labeled with `label`.
+    #[inline]
+    fn after(self, label: impl SystemLabel) -> SystemSetConfig {
+        let mut config = self.into_set_config();
+        config.after.push(Box::new(label));
+        config
+    }
+
+    /// Only runs systems in the set if `condition` returns `true`. Each system gets its own instance
+    /// of the condition, evaluated right before that system runs.
+    #[inline]
+    fn run_if<Cond: Condition<Marker> + Clone + 'static + Send + Sync, Marker>(self, condition: Cond) -> SystemSetConfig {
+        let mut config = self.into_set_config();
+        config.conditions.push(Box::new(move |world| Ok(Box::new(condition.clone().into_condition(world)?))));
+        config
+    }
+}
+
+impl IntoSystemSetConfig for SystemSetConfig {
+    #[inline]
+    fn into_set_config(self) -> SystemSetConfig {
+        self
+    }
+}
+
+impl<Set: SystemSet> IntoSystemSetConfig for Set {
+    #[inline]
+    fn into_set_config(self) -> SystemSetConfig {
+        SystemSetConfig {
+            set: Box::new(self),
+            before: Vec::new(),
+            after: Vec::new(),
+            conditions: Vec::new(),
+            applied: 0,
+        }
+    }
+}
+
+/// Runs a system only if every condition of the sets it's in returns `true`.
+pub(crate) struct SetConditionSystem {
+    system: BoxedSystem,
+    conditions: Vec<BoxedCondition>,
+    access: Access,
+}
+
+impl SetConditionSystem {
+    #[inline]
+    pub(crate) fn new(system: BoxedSystem, conditions: Vec<BoxedCondition>) -> Self {
+        let mut access = system.access().clone();
+        for condition in &conditions {
+            access.extend(condition.access());
+        }
+
+        Self { system, conditions, access, }
+    }
+
+    /// Creates the conditions of every set in `sets` that the system labeled with `labels` is in.
+    #[inline]
+    pub(crate) fn conditions(world: &mut World, labels: &[Box<dyn SystemLabel>], sets: &[SystemSetConfig]) -> anyhow::Result<Vec<BoxedCondition>> {
+        Self::init_conditions(world, labels, sets, |set| &set.conditions)
+    }
+
+    /// Like [`conditions`](Self::conditions), but only creates the ones added to the sets since
+    /// their systems were last initialized.
+    #[inline]
+    pub(crate) fn unapplied_conditions(world: &mut World, labels: &[Box<dyn SystemLabel>], sets: &[SystemSetConfig]) -> anyhow::Result<Vec<BoxedCondition>> {
+        Self::init_conditions(world, labels, sets, |set| &set.conditions[set.applied..])
+    }
+
+    #[inline]
+    fn init_conditions(
+        world: &mut World, labels: &[Box<dyn SystemLabel>], sets: &[SystemSetConfig],
+        select: fn(&SystemSetConfig) -> &[ConditionInit],
+    ) -> anyhow::Result<Vec<BoxedCondition>> {
+        sets.iter()
+            .filter(|set| labels.contains(&set.set))
+            .flat_map(select)
+            .map(|init| init(world))
+            .collect()
+    }
+
+    /// Wraps `system` to also check `conditions`, if there are any.
+    #[inline]
+    pub(crate) fn wrap(system: BoxedSystem, conditions: Vec<BoxedCondition>) -> BoxedSystem {
+        if conditions.is_empty() {
+            system
+        } else {
+            Box::new(Self::new(system, conditions))
+        }
+    }
+}
+
+impl System for SetConditionSystem {
+    type In = ();
+    type Out = ();
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        self.system.name()
+    }
+
+    #[inline]
+    fn access(&self) -> &Access {
+        &self.access
+    }
+
+    #[inline]
+    unsafe fn call_unchecked(&mut self, (): Self::In, world: WorldCell) -> anyhow::Result<Self::Out> {
+        for condition in &mut self.conditions {
+            let run = condition
+                .call_unchecked((), world)
+                .with_context(|| format!("run condition `{}` failed", condition.name()))?;
+
+            if !run { return Ok(()) }
+        }
+
+        self.system.call_unchecked((), world)
+    }
+
+    #[inline]
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        for condition in &mut self.conditions {
+            condition.apply(world)?;
+        }
+
+        self.system.apply(world)
+    }
+}
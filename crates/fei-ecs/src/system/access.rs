@@ -0,0 +1,216 @@
+use fei_common::prelude::*;
+use crate::{
+    component::ComponentId,
+    resource::{
+        ResourceId, ResourceLocalId,
+    },
+    world::World,
+};
+use fixedbitset::FixedBitSet;
+
+/// A component or (local) resource that two [`Access`]es conflict over.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AccessConflict {
+    Component(ComponentId),
+    Resource(ResourceId),
+    ResourceLocal(ResourceLocalId),
+}
+
+impl AccessConflict {
+    /// Describes the conflicting component or (local) resource by its type name.
+    pub fn describe(self, world: &World) -> String {
+        match self {
+            Self::Component(id) => format!("component `{}`", world.components().get_info(id).map_or("<unknown>", |info| info.name())),
+            Self::Resource(id) => format!("resource `{}`", world.resources().get_name(id).unwrap_or("<unknown>")),
+            Self::ResourceLocal(id) => format!("local resource `{}`", world.resources().get_local_name(id).unwrap_or("<unknown>")),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AccessConflictError {
+    #[error("system parameters `{}` and `{}` conflict in accessing {}", .first, .second, .target)]
+    Params {
+        first: &'static str,
+        second: &'static str,
+        target: String,
+    },
+    #[error("query `{}` conflicts with itself in accessing {}", .query, .target)]
+    Query {
+        query: &'static str,
+        target: String,
+    },
+}
+
+/// The set of components and resources a system reads and writes, used to decide whether two
+/// systems may run at the same time.
+#[derive(Default, Clone, Debug)]
+pub struct Access {
+    component_reads: FixedBitSet,
+    component_writes: FixedBitSet,
+    resource_reads: FixedBitSet,
+    resource_writes: FixedBitSet,
+    resource_local_reads: FixedBitSet,
+    resource_local_writes: FixedBitSet,
+    local: bool,
+    exclusive: bool,
+}
+
+impl Access {
+    #[inline]
+    pub fn add_component_read(&mut self, id: ComponentId) {
+        Self::add(&mut self.component_reads, id.0);
+    }
+
+    #[inline]
+    pub fn add_component_write(&mut self, id: ComponentId) {
+        Self::add(&mut self.component_writes, id.0);
+    }
+
+    #[inline]
+    pub fn add_resource_read(&mut self, id: ResourceId) {
+        Self::add(&mut self.resource_reads, id.0);
+    }
+
+    #[inline]
+    pub fn add_resource_write(&mut self, id: ResourceId) {
+        Self::add(&mut self.resource_writes, id.0);
+    }
+
+    /// Adds a read of a [local resource](crate::resource::ResourceLocal), also marking the access as
+    /// [local](Self::set_local).
+    #[inline]
+    pub fn add_resource_local_read(&mut self, id: ResourceLocalId) {
+        Self::add(&mut self.resource_local_reads, id.0);
+        self.local = true;
+    }
+
+    /// Adds a write of a [local resource](crate::resource::ResourceLocal), also marking the access as
+    /// [local](Self::set_local).
+    #[inline]
+    pub fn add_resource_local_write(&mut self, id: ResourceLocalId) {
+        Self::add(&mut self.resource_local_writes, id.0);
+        self.local = true;
+    }
+
+    /// Marks the access as touching [local resources](crate::resource::ResourceLocal), which may
+    /// only be accessed from the thread they're inserted from.
+    #[inline]
+    pub fn set_local(&mut self) {
+        self.local = true;
+    }
+
+    /// Marks the access as requiring the whole world mutably, e.g. for
+    /// [exclusive systems](crate::system::ExclusiveSystemFn). Exclusive accesses are compatible with
+    /// nothing.
+    #[inline]
+    pub fn set_exclusive(&mut self) {
+        self.exclusive = true;
+    }
+
+    #[inline]
+    pub fn reads_component(&self, id: ComponentId) -> bool {
+        self.component_reads.contains(id.0)
+    }
+
+    #[inline]
+    pub fn writes_component(&self, id: ComponentId) -> bool {
+        self.component_writes.contains(id.0)
+    }
+
+    #[inline]
+    pub fn reads_resource(&self, id: ResourceId) -> bool {
+        self.resource_reads.contains(id.0)
+    }
+
+    #[inline]
+    pub fn writes_resource(&self, id: ResourceId) -> bool {
+        self.resource_writes.contains(id.0)
+    }
+
+    #[inline]
+    pub fn reads_resource_local(&self, id: ResourceLocalId) -> bool {
+        self.resource_local_reads.contains(id.0)
+    }
+
+    #[inline]
+    pub fn writes_resource_local(&self, id: ResourceLocalId) -> bool {
+        self.resource_local_writes.contains(id.0)
+    }
+
+    #[inline]
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    #[inline]
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+
+    /// Returns whether both accesses may be held at the same time, i.e., neither is exclusive nor
+    /// writes anything the other reads or writes.
+    #[inline]
+    pub fn is_compatible(&self, other: &Access) -> bool {
+        !self.exclusive && !other.exclusive &&
+        self.component_writes.is_disjoint(&other.component_reads) &&
+        self.component_writes.is_disjoint(&other.component_writes) &&
+        other.component_writes.is_disjoint(&self.component_reads) &&
+        self.resource_writes.is_disjoint(&other.resource_reads) &&
+        self.resource_writes.is_disjoint(&other.resource_writes) &&
+        other.resource_writes.is_disjoint(&self.resource_reads) &&
+        self.resource_local_writes.is_disjoint(&other.resource_local_reads) &&
+        self.resource_local_writes.is_disjoint(&other.resource_local_writes) &&
+        other.resource_local_writes.is_disjoint(&self.resource_local_reads)
+    }
+
+    /// Returns a component or (local) resource that one access writes and the other reads or writes, if any.
+    pub fn get_conflict(&self, other: &Access) -> Option<AccessConflict> {
+        let first = |a: &FixedBitSet, b: &FixedBitSet| a.intersection(b).next();
+        first(&self.component_writes, &other.component_reads)
+            .or_else(|| first(&self.component_writes, &other.component_writes))
+            .or_else(|| first(&other.component_writes, &self.component_reads))
+            .map(|id| AccessConflict::Component(ComponentId(id)))
+            .or_else(|| first(&self.resource_writes, &other.resource_reads)
+                .or_else(|| first(&self.resource_writes, &other.resource_writes))
+                .or_else(|| first(&other.resource_writes, &self.resource_reads))
+                .map(|id| AccessConflict::Resource(ResourceId(id)))
+            )
+            .or_else(|| first(&self.resource_local_writes, &other.resource_local_reads)
+                .or_else(|| first(&self.resource_local_writes, &other.resource_local_writes))
+                .or_else(|| first(&other.resource_local_writes, &self.resource_local_reads))
+                .map(|id| AccessConflict::ResourceLocal(ResourceLocalId(id)))
+            )
+    }
+
+    /// Adds every access of `other` to this one.
+    #[inline]
+    pub fn extend(&mut self, other: &Access) {
+        self.component_reads.union_with(&other.component_reads);
+        self.component_writes.union_with(&other.component_writes);
+        self.resource_reads.union_with(&other.resource_reads);
+        self.resource_writes.union_with(&other.resource_writes);
+        self.resource_local_reads.union_with(&other.resource_local_reads);
+        self.resource_local_writes.union_with(&other.resource_local_writes);
+        self.local |= other.local;
+        self.exclusive |= other.exclusive;
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.component_reads.clear();
+        self.component_writes.clear();
+        self.resource_reads.clear();
+        self.resource_writes.clear();
+        self.resource_local_reads.clear();
+        self.resource_local_writes.clear();
+        self.local = false;
+        self.exclusive = false;
+    }
+
+    #[inline]
+    fn add(bits: &mut FixedBitSet, index: usize) {
+        bits.grow(index + 1);
+        bits.insert(index);
+    }
+}
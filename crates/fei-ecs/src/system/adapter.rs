@@ -0,0 +1,329 @@
+use fei_common::prelude::*;
+use crate::{
+    system::{
+        System, ReadOnlySystem, IntoSystem,
+        Access,
+    },
+    world::{
+        World, WorldCell,
+    },
+};
+use anyhow::Context;
+use parking_lot::Mutex;
+use std::{
+    marker::PhantomData,
+    sync::OnceLock,
+};
+
+/// Distinguishes piped and adapted systems in [`IntoSystem`] markers.
+pub struct IsCombined;
+
+/// Creates a [`PipeSystem`], see [`IntoSystem::pipe`].
+pub struct IntoPipeSystem<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> IntoPipeSystem<A, B> {
+    #[inline]
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second, }
+    }
+}
+
+impl<A: IntoSystem<AMarker>, B: IntoSystem<BMarker, In = A::Out>, AMarker, BMarker> IntoSystem<(IsCombined, AMarker, BMarker)> for IntoPipeSystem<A, B> {
+    type In = A::In;
+    type Out = B::Out;
+    type System = PipeSystem<A::System, B::System>;
+
+    #[inline]
+    fn into_system(self, world: &mut World) -> anyhow::Result<Self::System> {
+        Ok(PipeSystem::new(self.first.into_system(world)?, self.second.into_system(world)?))
+    }
+}
+
+/// Runs two systems one after the other, passing the output of the first one as the input of the
+/// second one. Both keep their own parameter states, and the combined [`Access`] is their union, as
+/// they never run at the same time.
+pub struct PipeSystem<A: System, B: System<In = A::Out>> {
+    first: A,
+    second: B,
+    name: &'static str,
+    access: Access,
+}
+
+impl<A: System, B: System<In = A::Out>> PipeSystem<A, B> {
+    #[inline]
+    pub fn new(first: A, second: B) -> Self {
+        let mut access = first.access().clone();
+        access.extend(second.access());
+
+        let name = intern(format!("{} | {}", first.name(), second.name()));
+        Self { first, second, name, access, }
+    }
+}
+
+/// Leaks combined names once, so rebuilding the same systems doesn't leak again.
+fn intern(name: String) -> &'static str {
+    static NAMES: OnceLock<Mutex<FxHashSet<&'static str>>> = OnceLock::new();
+
+    let mut names = NAMES.get_or_init(default).lock();
+    match names.get(name.as_str()) {
+        Some(&name) => name,
+        None => {
+            let name = &*Box::leak(name.into_boxed_str());
+            names.insert(name);
+            name
+        },
+    }
+}
+
+unsafe impl<A: ReadOnlySystem, B: ReadOnlySystem<In = A::Out>> ReadOnlySystem for PipeSystem<A, B> {}
+impl<A: System, B: System<In = A::Out>> System for PipeSystem<A, B> {
+    type In = A::In;
+    type Out = B::Out;
+
+    /// Returns the names of both systems, as in `first | second`.
+    #[inline]
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    #[inline]
+    fn access(&self) -> &Access {
+        &self.access
+    }
+
+    /// Calls both systems without applying the deferred state of the first one in between.
+    #[inline]
+    unsafe fn call_unchecked(&mut self, input: Self::In, world: WorldCell) -> anyhow::Result<Self::Out> {
+        let output = self.first
+            .call_unchecked(input, world)
+            .with_context(|| format!("piped system `{}` failed", self.first.name()))?;
+        self.second
+            .call_unchecked(output, world)
+            .with_context(|| format!("piped system `{}` failed", self.second.name()))
+    }
+
+    #[inline]
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        self.first.apply(world)?;
+        self.second.apply(world)
+    }
+}
+
+/// Transforms the result of a system, used by [`AdapterSystem`].
+pub trait Adapter<In>: 'static + Send + Sync {
+    type Out;
+
+    /// Transforms the result of the system of the given name.
+    fn adapt(&mut self, system: &'static str, result: anyhow::Result<In>) -> anyhow::Result<Self::Out>;
+}
+
+/// Transforms the output with a function, see [`IntoSystem::map`].
+pub struct Map<Func>(pub Func);
+impl<In, Out, Func: FnMut(In) -> Out + 'static + Send + Sync> Adapter<In> for Map<Func> {
+    type Out = Out;
+
+    #[inline]
+    fn adapt(&mut self, _: &'static str, result: anyhow::Result<In>) -> anyhow::Result<Self::Out> {
+        result.map(&mut self.0)
+    }
+}
+
+/// Discards errors, see [`IntoSystem::ignore_errors`].
+pub struct IgnoreErrors;
+impl Adapter<()> for IgnoreErrors {
+    type Out = ();
+
+    #[inline]
+    fn adapt(&mut self, _: &'static str, _: anyhow::Result<()>) -> anyhow::Result<Self::Out> {
+        Ok(())
+    }
+}
+
+/// Prints errors to the standard error, see [`IntoSystem::log_errors`].
+pub struct LogErrors;
+impl Adapter<()> for LogErrors {
+    type Out = ();
+
+    #[inline]
+    fn adapt(&mut self, system: &'static str, result: anyhow::Result<()>) -> anyhow::Result<Self::Out> {
+        if let Err(error) = result {
+            eprintln!("system `{system}` failed: {error:?}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Passes errors to a sink along with the name of the system, see [`IntoSystem::log_errors_with`].
+pub struct LogErrorsWith<Sink>(pub Sink);
+impl<Sink: FnMut(&'static str, anyhow::Error) + 'static + Send + Sync> Adapter<()> for LogErrorsWith<Sink> {
+    type Out = ();
+
+    #[inline]
+    fn adapt(&mut self, system: &'static str, result: anyhow::Result<()>) -> anyhow::Result<Self::Out> {
+        if let Err(error) = result {
+            (self.0)(system, error);
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces errors with a default output, see [`IntoSystem::unwrap_or`].
+pub struct UnwrapOr<T>(pub T);
+impl<T: 'static + Send + Sync + Clone> Adapter<T> for UnwrapOr<T> {
+    type Out = T;
+
+    #[inline]
+    fn adapt(&mut self, _: &'static str, result: anyhow::Result<T>) -> anyhow::Result<Self::Out> {
+        Ok(result.unwrap_or_else(|_| self.0.clone()))
+    }
+}
+
+/// Creates an [`AdapterSystem`], e.g. with [`IntoSystem::map`].
+pub struct IntoAdapterSystem<S, A> {
+    system: S,
+    adapter: A,
+}
+
+impl<S, A> IntoAdapterSystem<S, A> {
+    #[inline]
+    pub fn new(system: S, adapter: A) -> Self {
+        Self { system, adapter, }
+    }
+}
+
+impl<S: IntoSystem<Marker>, A: Adapter<S::Out>, Marker> IntoSystem<(IsCombined, Marker)> for IntoAdapterSystem<S, A> {
+    type In = S::In;
+    type Out = A::Out;
+    type System = AdapterSystem<S::System, A>;
+
+    #[inline]
+    fn into_system(self, world: &mut World) -> anyhow::Result<Self::System> {
+        Ok(AdapterSystem {
+            system: self.system.into_system(world)?,
+            adapter: self.adapter,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Transforms the result of a system with an [`Adapter`].
+pub struct AdapterSystem<S: System, A: Adapter<S::Out>> {
+    system: S,
+    adapter: A,
+    _marker: PhantomData<fn() -> S::Out>,
+}
+
+unsafe impl<S: ReadOnlySystem, A: Adapter<S::Out>> ReadOnlySystem for AdapterSystem<S, A> {}
+impl<S: System, A: Adapter<S::Out>> System for AdapterSystem<S, A> {
+    type In = S::In;
+    type Out = A::Out;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        self.system.name()
+    }
+
+    #[inline]
+    fn access(&self) -> &Access {
+        self.system.access()
+    }
+
+    #[inline]
+    unsafe fn call_unchecked(&mut self, input: Self::In, world: WorldCell) -> anyhow::Result<Self::Out> {
+        let result = self.system.call_unchecked(input, world);
+        self.adapter.adapt(self.system.name(), result)
+    }
+
+    #[inline]
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        self.system.apply(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_ecs_macros::{
+        Component, Resource,
+    };
+    use crate::{
+        query::Query,
+        resource::{
+            Res, ResMut,
+        },
+        system::In,
+    };
+    use std::sync::{
+        Arc, Mutex,
+    };
+
+    #[derive(Component)]
+    struct A(u32);
+    #[derive(Resource)]
+    struct Factor(u32);
+    #[derive(Resource, Default)]
+    struct Total(u32);
+
+    fn sum(query: Query<&A>) -> anyhow::Result<u32> {
+        Ok(query.iter().map(|a| a.0).sum())
+    }
+
+    fn scale(In(value): In<u32>, factor: Res<Factor>) -> anyhow::Result<u32> {
+        Ok(value * factor.0)
+    }
+
+    fn store(In(value): In<u32>, mut total: ResMut<Total>) -> anyhow::Result<()> {
+        total.0 = value;
+        Ok(())
+    }
+
+    fn fail(In(message): In<&'static str>) -> anyhow::Result<()> {
+        anyhow::bail!(message)
+    }
+
+    #[test]
+    fn adapters() -> anyhow::Result<()> {
+        let mut world = World::default();
+        world.spawn(A(1))?;
+        world.spawn(A(2))?;
+        world.insert_res(Factor(3));
+        world.init_res::<Total>();
+
+        let mut piped = sum.pipe(scale).map(|value| value + 1).pipe(store).into_system(&mut world)?;
+        piped.call((), &mut world)?;
+        assert_eq!(world.res::<Total>().map(|total| total.0), Some(10));
+
+        // Piped systems access what either of them do.
+        let a = world.register_component::<A>();
+        let [factor, total] = [world.register_res::<Factor>(), world.register_res::<Total>()];
+        let access = piped.access();
+        assert!(access.reads_component(a) && access.reads_resource(factor) && access.writes_resource(total));
+
+        let mut ignored = fail.ignore_errors().into_system(&mut world)?;
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut printed = fail.log_errors().into_system(&mut world)?;
+        let mut logged = fail.log_errors_with({
+            let log = log.clone();
+            move |system, error| log.lock().unwrap().push((system, error.to_string()))
+        }).into_system(&mut world)?;
+        let mut failing = fail.map(|()| 1).unwrap_or(0).into_system(&mut world)?;
+        let mut piped = fail.pipe(|In(()): In<()>| Ok(())).into_system(&mut world)?;
+        ignored.call("ignored", &mut world)?;
+        printed.call("printed", &mut world)?;
+        logged.call("logged", &mut world)?;
+        assert!(matches!(&log.lock().unwrap()[..], [(system, error)] if system.contains("fail") && error == "logged"));
+        assert_eq!(failing.call("failing", &mut world)?, 0);
+
+        // Piped systems are named after both systems, and name whichever of them fails.
+        let (first, second) = piped.name().split_once(" | ").unwrap();
+        assert!(first.ends_with("fail") && second.contains("adapters"));
+        let error = piped.call("piped", &mut world).unwrap_err();
+        assert!(error.to_string().contains(first) && error.root_cause().to_string() == "piped");
+        Ok(())
+    }
+}
@@ -0,0 +1,372 @@
+use fei_common::prelude::*;
+use crate::{
+    resource::{
+        Resource, Res,
+    },
+    system::{
+        System, ReadOnlySystem, IntoSystem, IsCombined,
+        Access,
+    },
+    world::{
+        World, WorldCell,
+    },
+    ChangeAware,
+};
+use anyhow::Context;
+
+pub type BoxedCondition = Box<dyn ReadOnlySystem<In = (), Out = bool>>;
+
+/// A [read-only](ReadOnlySystem) system with no input that returns whether another system should
+/// run, see [`IntoSystem::run_if`]. Implemented for every such [`IntoSystem`], e.g. functions taking
+/// only [`ReadOnlySystemParam`](crate::system::ReadOnlySystemParam)s and returning
+/// `anyhow::Result<bool>`.
+pub trait Condition<Marker>: Sized {
+    type System: ReadOnlySystem<In = (), Out = bool>;
+
+    fn into_condition(self, world: &mut World) -> anyhow::Result<Self::System>;
+}
+
+impl<Cond: IntoSystem<Marker, In = (), Out = bool>, Marker> Condition<Marker> for Cond
+where
+    Cond::System: ReadOnlySystem,
+{
+    type System = <Cond as IntoSystem<Marker>>::System;
+
+    #[inline]
+    fn into_condition(self, world: &mut World) -> anyhow::Result<Self::System> {
+        self.into_system(world)
+    }
+}
+
+/// Creates a [`RunIfSystem`], see [`IntoSystem::run_if`].
+pub struct IntoRunIfSystem<S, Cond> {
+    system: S,
+    condition: Cond,
+}
+
+impl<S, Cond> IntoRunIfSystem<S, Cond> {
+    #[inline]
+    pub fn new(system: S, condition: Cond) -> Self {
+        Self { system, condition, }
+    }
+}
+
+impl<S: IntoSystem<SMarker>, Cond: Condition<CondMarker>, SMarker, CondMarker> IntoSystem<(IsCombined, SMarker, CondMarker)> for IntoRunIfSystem<S, Cond>
+where
+    S::Out: Default,
+{
+    type In = S::In;
+    type Out = S::Out;
+    type System = RunIfSystem<S::System, Cond::System>;
+
+    #[inline]
+    fn into_system(self, world: &mut World) -> anyhow::Result<Self::System> {
+        Ok(RunIfSystem::new(self.system.into_system(world)?, self.condition.into_condition(world)?))
+    }
+}
+
+/// Runs a system only if its condition returns `true`, outputting [`Default::default`] otherwise.
+/// The combined [`Access`] is the union of both.
+pub struct RunIfSystem<S: System, Cond: ReadOnlySystem<In = (), Out = bool>> {
+    system: S,
+    condition: Cond,
+    access: Access,
+}
+
+impl<S: System, Cond: ReadOnlySystem<In = (), Out = bool>> RunIfSystem<S, Cond> {
+    #[inline]
+    pub fn new(system: S, condition: Cond) -> Self {
+        let mut access = system.access().clone();
+        access.extend(condition.access());
+
+        Self { system, condition, access, }
+    }
+}
+
+unsafe impl<S: ReadOnlySystem, Cond: ReadOnlySystem<In = (), Out = bool>> ReadOnlySystem for RunIfSystem<S, Cond>
+where
+    S::Out: Default,
+{}
+
+impl<S: System, Cond: ReadOnlySystem<In = (), Out = bool>> System for RunIfSystem<S, Cond>
+where
+    S::Out: Default,
+{
+    type In = S::In;
+    type Out = S::Out;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        self.system.name()
+    }
+
+    #[inline]
+    fn access(&self) -> &Access {
+        &self.access
+    }
+
+    #[inline]
+    unsafe fn call_unchecked(&mut self, input: Self::In, world: WorldCell) -> anyhow::Result<Self::Out> {
+        let run = self.condition
+            .call_unchecked((), world)
+            .with_context(|| format!("run condition `{}` failed", self.condition.name()))?;
+
+        if run {
+            self.system.call_unchecked(input, world)
+        } else {
+            Ok(default())
+        }
+    }
+
+    #[inline]
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        self.condition.apply(world)?;
+        self.system.apply(world)
+    }
+}
+
+/// Creates a [`NotCondition`], see [`not`].
+#[derive(Clone)]
+pub struct IntoNotCondition<Cond>(Cond);
+impl<Cond: Condition<Marker>, Marker> IntoSystem<(IsCombined, Marker)> for IntoNotCondition<Cond> {
+    type In = ();
+    type Out = bool;
+    type System = NotCondition<Cond::System>;
+
+    #[inline]
+    fn into_system(self, world: &mut World) -> anyhow::Result<Self::System> {
+        Ok(NotCondition(self.0.into_condition(world)?))
+    }
+}
+
+/// Negates the output of a condition.
+pub struct NotCondition<Cond: ReadOnlySystem<In = (), Out = bool>>(Cond);
+
+unsafe impl<Cond: ReadOnlySystem<In = (), Out = bool>> ReadOnlySystem for NotCondition<Cond> {}
+impl<Cond: ReadOnlySystem<In = (), Out = bool>> System for NotCondition<Cond> {
+    type In = ();
+    type Out = bool;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    #[inline]
+    fn access(&self) -> &Access {
+        self.0.access()
+    }
+
+    #[inline]
+    unsafe fn call_unchecked(&mut self, (): Self::In, world: WorldCell) -> anyhow::Result<Self::Out> {
+        Ok(!self.0.call_unchecked((), world)?)
+    }
+
+    #[inline]
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        self.0.apply(world)
+    }
+}
+
+/// Creates a [`CombinedCondition`], see [`and`] and [`or`].
+#[derive(Clone)]
+pub struct IntoCombinedCondition<A, B> {
+    first: A,
+    second: B,
+    short_circuit: bool,
+}
+
+impl<A: Condition<AMarker>, B: Condition<BMarker>, AMarker, BMarker> IntoSystem<(IsCombined, AMarker, BMarker)> for IntoCombinedCondition<A, B> {
+    type In = ();
+    type Out = bool;
+    type System = CombinedCondition<A::System, B::System>;
+
+    #[inline]
+    fn into_system(self, world: &mut World) -> anyhow::Result<Self::System> {
+        let first = self.first.into_condition(world)?;
+        let second = self.second.into_condition(world)?;
+
+        let mut access = first.access().clone();
+        access.extend(second.access());
+
+        Ok(CombinedCondition {
+            first,
+            second,
+            access,
+            short_circuit: self.short_circuit,
+        })
+    }
+}
+
+/// Combines the outputs of two conditions, only running the second one if the first one doesn't
+/// already decide the output.
+pub struct CombinedCondition<A: ReadOnlySystem<In = (), Out = bool>, B: ReadOnlySystem<In = (), Out = bool>> {
+    first: A,
+    second: B,
+    access: Access,
+    /// The output of `first` that is returned without running `second`: `false` for [`and`], `true`
+    /// for [`or`].
+    short_circuit: bool,
+}
+
+unsafe impl<A: ReadOnlySystem<In = (), Out = bool>, B: ReadOnlySystem<In = (), Out = bool>> ReadOnlySystem for CombinedCondition<A, B> {}
+impl<A: ReadOnlySystem<In = (), Out = bool>, B: ReadOnlySystem<In = (), Out = bool>> System for CombinedCondition<A, B> {
+    type In = ();
+    type Out = bool;
+
+    /// Returns the name of the first condition.
+    #[inline]
+    fn name(&self) -> &'static str {
+        self.first.name()
+    }
+
+    #[inline]
+    fn access(&self) -> &Access {
+        &self.access
+    }
+
+    #[inline]
+    unsafe fn call_unchecked(&mut self, (): Self::In, world: WorldCell) -> anyhow::Result<Self::Out> {
+        if self.first.call_unchecked((), world)? == self.short_circuit {
+            Ok(self.short_circuit)
+        } else {
+            self.second.call_unchecked((), world)
+        }
+    }
+
+    #[inline]
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        self.first.apply(world)?;
+        self.second.apply(world)
+    }
+}
+
+/// Returns `true` if the resource `T` is present.
+#[inline]
+pub fn resource_exists<T: Resource>(res: Option<Res<T>>) -> anyhow::Result<bool> {
+    Ok(res.is_some())
+}
+
+/// Returns `true` if the resource `T` has been inserted since the condition last ran.
+#[inline]
+pub fn resource_added<T: Resource>(res: Option<Res<T>>) -> anyhow::Result<bool> {
+    Ok(res.is_some_and(|res| res.is_added()))
+}
+
+/// Returns `true` if the resource `T` has been inserted or mutated since the condition last ran.
+#[inline]
+pub fn resource_changed<T: Resource>(res: Option<Res<T>>) -> anyhow::Result<bool> {
+    Ok(res.is_some_and(|res| res.is_updated()))
+}
+
+/// Returns `true` if `condition` returns `false`.
+#[inline]
+pub fn not<Cond: Condition<Marker>, Marker>(condition: Cond) -> IntoNotCondition<Cond> {
+    IntoNotCondition(condition)
+}
+
+/// Returns `true` if both conditions do. `second` only runs if `first` returns `true`.
+#[inline]
+pub fn and<A: Condition<AMarker>, B: Condition<BMarker>, AMarker, BMarker>(first: A, second: B) -> IntoCombinedCondition<A, B> {
+    IntoCombinedCondition { first, second, short_circuit: false, }
+}
+
+/// Returns `true` if either condition does. `second` only runs if `first` returns `false`.
+#[inline]
+pub fn or<A: Condition<AMarker>, B: Condition<BMarker>, AMarker, BMarker>(first: A, second: B) -> IntoCombinedCondition<A, B> {
+    IntoCombinedCondition { first, second, short_circuit: true, }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_ecs_macros::Resource;
+    use crate::{
+        resource::ResMut,
+        schedule::Schedule,
+    };
+
+    #[derive(Resource, Default)]
+    struct Count(u32);
+    #[derive(Resource)]
+    struct Enabled;
+    #[derive(Resource)]
+    struct Paused;
+
+    fn count(mut count: ResMut<Count>) -> anyhow::Result<()> {
+        count.0 += 1;
+        Ok(())
+    }
+
+    fn counted(world: &World) -> Option<u32> {
+        world.res::<Count>().map(|count| count.0)
+    }
+
+    #[test]
+    fn run_if() -> anyhow::Result<()> {
+        let mut world = World::default();
+        world.init_res::<Count>();
+
+        let mut system = count.run_if(resource_exists::<Enabled>).into_system(&mut world)?;
+        system.call((), &mut world)?;
+        assert_eq!(counted(&world), Some(0));
+
+        world.insert_res(Enabled);
+        system.call((), &mut world)?;
+        assert_eq!(counted(&world), Some(1));
+
+        // Conditions access what they read.
+        let [count, enabled] = [world.register_res::<Count>(), world.register_res::<Enabled>()];
+        assert!(system.access().writes_resource(count) && system.access().reads_resource(enabled));
+
+        // Skipped systems output the default.
+        let mut skipped = (|| anyhow::Ok(1u32)).run_if(resource_exists::<Paused>).into_system(&mut world)?;
+        assert_eq!(skipped.call((), &mut world)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn change_conditions() -> anyhow::Result<()> {
+        let mut world = World::default();
+        let mut added = resource_added::<Enabled>.into_system(&mut world)?;
+        let mut changed = resource_changed::<Count>.into_system(&mut world)?;
+        assert!(!added.call((), &mut world)? && !changed.call((), &mut world)?);
+
+        world.sync_change_mark();
+        world.insert_res(Enabled);
+        world.init_res::<Count>();
+        assert!(added.call((), &mut world)? && changed.call((), &mut world)?);
+
+        world.sync_change_mark();
+        assert!(!added.call((), &mut world)? && !changed.call((), &mut world)?);
+
+        world.sync_change_mark();
+        world.res_mut::<Count>().unwrap().0 = 1;
+        assert!(!added.call((), &mut world)? && changed.call((), &mut world)?);
+        Ok(())
+    }
+
+    #[test]
+    fn combinators() -> anyhow::Result<()> {
+        let mut world = World::default();
+        world.init_res::<Count>();
+
+        let mut schedule = Schedule::default();
+        schedule
+            .add_system(count.run_if(not(resource_exists::<Paused>)))
+            .add_system(count.run_if(and(resource_exists::<Enabled>, not(resource_exists::<Paused>))))
+            .add_system(count.run_if(or(resource_exists::<Enabled>, resource_exists::<Paused>)));
+
+        schedule.run(&mut world)?;
+        assert_eq!(counted(&world), Some(1));
+
+        world.insert_res(Enabled);
+        schedule.run(&mut world)?;
+        assert_eq!(counted(&world), Some(4));
+
+        world.insert_res(Paused);
+        schedule.run(&mut world)?;
+        assert_eq!(counted(&world), Some(5));
+        Ok(())
+    }
+}
@@ -1,23 +1,57 @@
 use fei_common::prelude::*;
 use crate::{
+    system::{
+        Access, AccessConflictError,
+        IntoPipeSystem, IntoAdapterSystem, IntoRunIfSystem,
+        Condition, Adapter, Map, IgnoreErrors, LogErrors, LogErrorsWith, UnwrapOr,
+    },
     world::{
         World, WorldCell,
     },
     ChangeMark,
 };
+use std::any::type_name;
+
+pub type BoxedSystem<In = (), Out = ()> = Box<dyn System<In = In, Out = Out>>;
 
 pub trait System: 'static + Send + Sync {
     type In;
     type Out;
 
+    /// Returns the name of the system, used in diagnostics.
+    #[inline]
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+
     #[inline]
     fn call(&mut self, input: Self::In, world: &mut World) -> anyhow::Result<Self::Out> {
-        unsafe { self.call_unchecked(input, world.cell_mut()) }
+        let out = unsafe { self.call_unchecked(input, world.cell_mut()) }?;
+        self.apply(world)?;
+        Ok(out)
     }
 
+    /// Returns the components and resources the system accesses.
+    fn access(&self) -> &Access;
+
+    /// Calls the system without applying its deferred state, e.g. queued [commands](crate::command::Commands).
     unsafe fn call_unchecked(&mut self, input: Self::In, world: WorldCell) -> anyhow::Result<Self::Out>;
+
+    /// Applies the system's deferred state to the world. [`call`](System::call) does this automatically.
+    #[inline]
+    fn apply(&mut self, _: &mut World) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
+/// A [`System`] that only reads the components and resources in its [`Access`], e.g. a
+/// [`SystemFn`] whose parameters are all [`ReadOnlySystemParam`]s. Required by
+/// [run conditions](crate::system::Condition).
+///
+/// # Safety
+/// The system must not mutate anything in the world it's called with, other than its own state.
+pub unsafe trait ReadOnlySystem: System {}
+
 pub trait SystemParam: Sized {
     type State: 'static + Send + Sync;
     type Item<'w, 's>: SystemParam<State = Self::State>;
@@ -25,7 +59,15 @@ pub trait SystemParam: Sized {
 
     unsafe fn construct<'w, 's>(world: WorldCell<'w>, state: &'s mut Self::State, last: ChangeMark, current: ChangeMark) -> anyhow::Result<Self::Item<'w, 's>>;
 
-    fn construct_state(world: &mut World) -> anyhow::Result<Self::State>;
+    /// Creates the persistent state, registering the components and resources the parameter
+    /// accesses to `access`.
+    fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State>;
+
+    /// Applies deferred operations recorded in the state, e.g. queued [commands](crate::command::Commands).
+    #[inline]
+    fn apply(_: &mut Self::State, _: &mut World) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
 pub unsafe trait ReadOnlySystemParam: SystemParam {}
@@ -45,8 +87,38 @@ macro_rules! impl_system_param {
 
             #[inline]
             #[allow(unused)]
-            fn construct_state(world: &mut World) -> anyhow::Result<Self::State> {
-                Ok(($($tuple_type::construct_state(world)?,)*))
+            fn construct_state(world: &mut World, access: &mut Access) -> anyhow::Result<Self::State> {
+                // Each parameter may not conflict with the preceding ones, e.g. `(ResMut<T>, Res<T>)`.
+                let mut params = Vec::<(&'static str, Access)>::new();
+                let state = ($({
+                    let name = type_name::<$tuple_type>();
+                    let mut param = Access::default();
+                    let state = $tuple_type::construct_state(world, &mut param)?;
+
+                    if let Some((first, conflict)) = params.iter().find_map(|(first, prev)| Some((*first, prev.get_conflict(&param)?))) {
+                        return Err(AccessConflictError::Params {
+                            first,
+                            second: name,
+                            target: conflict.describe(world),
+                        }.into());
+                    }
+
+                    params.push((name, param));
+                    state
+                },)*);
+
+                for (_, param) in &params {
+                    access.extend(param);
+                }
+
+                Ok(state)
+            }
+
+            #[inline]
+            #[allow(unused)]
+            fn apply(state: &mut Self::State, world: &mut World) -> anyhow::Result<()> {
+                $($tuple_type::apply(&mut state.$tuple_index, world)?;)*
+                Ok(())
             }
         }
 
@@ -60,6 +132,66 @@ pub trait IntoSystem<Marker>: Sized {
     type System: System<In = Self::In, Out = Self::Out>;
 
     fn into_system(self, world: &mut World) -> anyhow::Result<Self::System>;
+
+    /// Passes the output of this system as the input of `other`, running both one after the other
+    /// as a single system.
+    #[inline]
+    fn pipe<Other: IntoSystem<OtherMarker, In = Self::Out>, OtherMarker>(self, other: Other) -> IntoPipeSystem<Self, Other> {
+        IntoPipeSystem::new(self, other)
+    }
+
+    /// Only runs this system if `condition` returns `true` beforehand, outputting
+    /// [`Default::default`] otherwise.
+    #[inline]
+    fn run_if<Cond: Condition<CondMarker>, CondMarker>(self, condition: Cond) -> IntoRunIfSystem<Self, Cond>
+    where
+        Self::Out: Default,
+    {
+        IntoRunIfSystem::new(self, condition)
+    }
+
+    /// Transforms the output of this system with `func`.
+    #[inline]
+    fn map<T, Func: FnMut(Self::Out) -> T + 'static + Send + Sync>(self, func: Func) -> IntoAdapterSystem<Self, Map<Func>> {
+        IntoAdapterSystem::new(self, Map(func))
+    }
+
+    /// Discards errors this system returns.
+    #[inline]
+    fn ignore_errors(self) -> IntoAdapterSystem<Self, IgnoreErrors>
+    where
+        IgnoreErrors: Adapter<Self::Out>,
+    {
+        IntoAdapterSystem::new(self, IgnoreErrors)
+    }
+
+    /// Prints errors this system returns to the standard error instead of returning them.
+    #[inline]
+    fn log_errors(self) -> IntoAdapterSystem<Self, LogErrors>
+    where
+        LogErrors: Adapter<Self::Out>,
+    {
+        IntoAdapterSystem::new(self, LogErrors)
+    }
+
+    /// Passes errors this system returns to `sink` along with its name instead of returning them,
+    /// e.g. to forward them to a logger.
+    #[inline]
+    fn log_errors_with<Sink>(self, sink: Sink) -> IntoAdapterSystem<Self, LogErrorsWith<Sink>>
+    where
+        LogErrorsWith<Sink>: Adapter<Self::Out>,
+    {
+        IntoAdapterSystem::new(self, LogErrorsWith(sink))
+    }
+
+    /// Outputs `default` whenever this system returns an error.
+    #[inline]
+    fn unwrap_or(self, default: Self::Out) -> IntoAdapterSystem<Self, UnwrapOr<Self::Out>>
+    where
+        UnwrapOr<Self::Out>: Adapter<Self::Out>,
+    {
+        IntoAdapterSystem::new(self, UnwrapOr(default))
+    }
 }
 
 pub trait SystemFn<Marker>: 'static + Send + Sync + Sized {
@@ -72,6 +204,7 @@ pub trait SystemFn<Marker>: 'static + Send + Sync + Sized {
 
 pub struct SystemFnImpl<Func: SystemFn<Marker>, Marker: 'static> {
     state: <Func::Param as SystemParam>::State,
+    access: Access,
     func: Func,
     last: ChangeMark,
 }
@@ -80,23 +213,49 @@ impl<Func: SystemFn<Marker>, Marker> System for SystemFnImpl<Func, Marker> {
     type In = Func::In;
     type Out = Func::Out;
 
+    #[inline]
+    fn name(&self) -> &'static str {
+        type_name::<Func>()
+    }
+
+    #[inline]
+    fn access(&self) -> &Access {
+        &self.access
+    }
+
     #[inline]
     unsafe fn call_unchecked(&mut self, input: Self::In, world: WorldCell) -> anyhow::Result<Self::Out> {
         let current = world.get().change_mark();
         let last = std::mem::replace(&mut self.last, current);
         self.func.call(input, world, &mut self.state, last, current)
     }
+
+    #[inline]
+    fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        Func::Param::apply(&mut self.state, world)
+    }
 }
 
-impl<Func: SystemFn<Marker>, Marker: 'static> IntoSystem<Marker> for Func {
+unsafe impl<Func: SystemFn<Marker>, Marker> ReadOnlySystem for SystemFnImpl<Func, Marker>
+where
+    Func::Param: ReadOnlySystemParam,
+{}
+
+/// Distinguishes [`SystemFn`]s from [`ExclusiveSystemFn`](crate::system::ExclusiveSystemFn)s in
+/// [`IntoSystem`] markers.
+pub struct IsSystemFn;
+
+impl<Func: SystemFn<Marker>, Marker: 'static> IntoSystem<(IsSystemFn, Marker)> for Func {
     type In = Func::In;
     type Out = Func::Out;
     type System = SystemFnImpl<Func, Marker>;
 
     #[inline]
     fn into_system(self, world: &mut World) -> anyhow::Result<Self::System> {
+        let mut access = Access::default();
         Ok(SystemFnImpl {
-            state: Func::Param::construct_state(world)?,
+            state: Func::Param::construct_state(world, &mut access)?,
+            access,
             func: self,
             last: world.last_change_mark(),
         })
@@ -144,10 +303,16 @@ macro_rules! impl_system_fn {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use fei_ecs_macros::Resource;
+    use fei_ecs_macros::{
+        Component, Resource, ResourceLocal,
+    };
     use crate::{
+        query::{
+            Query, With,
+        },
         resource::{
             Res, ResMut,
+            ResLocal, ResLocalMut,
         },
         ChangeAware,
     };
@@ -216,4 +381,110 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn access() -> anyhow::Result<()> {
+        #[derive(Component)]
+        struct A;
+        #[derive(Component)]
+        struct B;
+        #[derive(Component)]
+        struct C;
+        #[derive(Resource)]
+        struct R;
+
+        fn writer(_: Query<(&mut A, &B), With<C>>, _: Res<R>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn reader(_: Query<&A>, _: ResMut<R>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn other(_: Query<(&B, &C)>, _: Res<R>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        let mut world = World::default();
+        let writer = writer.into_system(&mut world)?;
+        let reader = reader.into_system(&mut world)?;
+        let other = other.into_system(&mut world)?;
+
+        let [a, b, c] = [world.register_component::<A>(), world.register_component::<B>(), world.register_component::<C>()];
+        let r = world.register_res::<R>();
+
+        let access = writer.access();
+        assert!(access.writes_component(a) && !access.reads_component(a));
+        assert!(access.reads_component(b) && !access.writes_component(b));
+        assert!(!access.reads_component(c) && !access.writes_component(c));
+        assert!(access.reads_resource(r) && !access.writes_resource(r));
+
+        assert!(!writer.access().is_compatible(reader.access()));
+        assert!(writer.access().is_compatible(other.access()));
+        assert!(!reader.access().is_compatible(other.access()));
+        Ok(())
+    }
+
+    #[test]
+    fn conflicts() -> anyhow::Result<()> {
+        #[derive(Component)]
+        struct A;
+        #[derive(Component)]
+        struct B;
+        #[derive(Resource)]
+        struct R;
+        #[derive(ResourceLocal)]
+        struct L;
+
+        fn res(_: ResMut<R>, _: Res<R>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn locals(_: ResLocalMut<L>, _: ResLocal<L>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn local_reader(_: ResLocal<L>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn local_writer(_: ResLocalMut<L>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn queries(_: Query<&A>, _: Query<(&mut A, &B)>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn query(_: Query<(&mut A, Option<&A>)>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn shared(_: Query<&A, With<B>>, _: Query<(&A, &B)>, _: Res<R>, _: Res<R>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        let mut world = World::default();
+        let error = res.into_system(&mut world).err().unwrap().to_string();
+        assert!(error.contains("ResMut<") && error.contains("Res<") && error.contains("resource") && error.contains("::R`"));
+
+        let error = locals.into_system(&mut world).err().unwrap().to_string();
+        assert!(error.contains("ResLocalMut<") && error.contains("local resource") && error.contains("::L`"));
+
+        let local_reader = local_reader.into_system(&mut world)?;
+        let local_writer = local_writer.into_system(&mut world)?;
+        let l = world.register_res_local::<L>();
+        assert!(local_reader.access().reads_resource_local(l) && local_writer.access().writes_resource_local(l));
+        assert!(local_reader.access().is_local() && !local_reader.access().is_compatible(local_writer.access()));
+
+        let error = queries.into_system(&mut world).err().unwrap().to_string();
+        assert!(error.contains("A>` and `") && error.contains("(&mut") && error.contains("component") && error.contains("::A`"));
+
+        let error = query.into_system(&mut world).err().unwrap().to_string();
+        assert!(error.contains("conflicts with itself") && error.contains("::A`"));
+
+        // Shared reads never conflict.
+        assert!(shared.into_system(&mut world).is_ok());
+        Ok(())
+    }
 }
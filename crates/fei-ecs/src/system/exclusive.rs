@@ -0,0 +1,159 @@
+use fei_common::prelude::*;
+use crate::{
+    system::{
+        System, IntoSystem, In,
+        Access,
+    },
+    world::{
+        World, WorldCell,
+    },
+};
+use std::{
+    any::type_name,
+    marker::PhantomData,
+};
+
+/// Functions taking the whole [`World`] mutably, i.e. `FnMut(&mut World) -> anyhow::Result<Out>`,
+/// optionally preceded by an [`In<T>`] parameter. Their systems are barriers that never run in
+/// parallel with any other system.
+pub trait ExclusiveSystemFn<Marker>: 'static + Send + Sync + Sized {
+    type In;
+    type Out;
+
+    fn call(&mut self, input: Self::In, world: &mut World) -> anyhow::Result<Self::Out>;
+}
+
+impl<Func, Out> ExclusiveSystemFn<fn(&mut World) -> anyhow::Result<Out>> for Func where
+    Func: FnMut(&mut World) -> anyhow::Result<Out> + 'static + Send + Sync,
+{
+    type In = ();
+    type Out = Out;
+
+    #[inline]
+    fn call(&mut self, (): Self::In, world: &mut World) -> anyhow::Result<Self::Out> {
+        (self)(world)
+    }
+}
+
+impl<Func, Input, Out> ExclusiveSystemFn<fn(In<Input>, &mut World) -> anyhow::Result<Out>> for Func where
+    Func: FnMut(In<Input>, &mut World) -> anyhow::Result<Out> + 'static + Send + Sync,
+{
+    type In = Input;
+    type Out = Out;
+
+    #[inline]
+    fn call(&mut self, input: Self::In, world: &mut World) -> anyhow::Result<Self::Out> {
+        (self)(In(input), world)
+    }
+}
+
+/// Distinguishes [`ExclusiveSystemFn`]s from [`SystemFn`](crate::system::SystemFn)s in
+/// [`IntoSystem`] markers.
+pub struct IsExclusive;
+
+pub struct ExclusiveSystemFnImpl<Func: ExclusiveSystemFn<Marker>, Marker: 'static> {
+    access: Access,
+    func: Func,
+    _marker: PhantomData<fn() -> Marker>,
+}
+
+impl<Func: ExclusiveSystemFn<Marker>, Marker> System for ExclusiveSystemFnImpl<Func, Marker> {
+    type In = Func::In;
+    type Out = Func::Out;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        type_name::<Func>()
+    }
+
+    #[inline]
+    fn call(&mut self, input: Self::In, world: &mut World) -> anyhow::Result<Self::Out> {
+        self.func.call(input, world)
+    }
+
+    #[inline]
+    fn access(&self) -> &Access {
+        &self.access
+    }
+
+    /// # Safety
+    /// The world must be [writable](WorldCell::write), and there may not be any other access to it
+    /// while the system runs.
+    #[inline]
+    unsafe fn call_unchecked(&mut self, input: Self::In, world: WorldCell) -> anyhow::Result<Self::Out> {
+        self.func.call(input, world.get_mut())
+    }
+}
+
+impl<Func: ExclusiveSystemFn<Marker>, Marker: 'static> IntoSystem<(IsExclusive, Marker)> for Func {
+    type In = Func::In;
+    type Out = Func::Out;
+    type System = ExclusiveSystemFnImpl<Func, Marker>;
+
+    #[inline]
+    fn into_system(self, _: &mut World) -> anyhow::Result<Self::System> {
+        let mut access = Access::default();
+        access.set_exclusive();
+
+        Ok(ExclusiveSystemFnImpl {
+            access,
+            func: self,
+            _marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_ecs_macros::{
+        Component, Resource,
+    };
+    use crate::{
+        query::Query,
+        system::IntoSystem,
+    };
+
+    #[derive(Component, Debug, PartialEq)]
+    struct A(u32);
+    #[derive(Resource)]
+    struct Spawned(usize);
+
+    #[test]
+    fn exclusive() -> anyhow::Result<()> {
+        fn spawn(In(count): In<u32>, world: &mut World) -> anyhow::Result<usize> {
+            for i in 0..count {
+                world.spawn(A(i))?;
+            }
+
+            let spawned = world.query::<&A>().iter(world).count();
+            world.insert_res(Spawned(spawned));
+            Ok(spawned)
+        }
+
+        fn count(query: Query<&A>) -> anyhow::Result<usize> {
+            Ok(query.iter().count())
+        }
+
+        let mut world = World::default();
+        let mut spawn = spawn.into_system(&mut world)?;
+        let mut count = count.into_system(&mut world)?;
+        let mut clear = (|world: &mut World| {
+            world.remove_res::<Spawned>();
+            Ok(())
+        }).into_system(&mut world)?;
+
+        assert!(spawn.access().is_exclusive());
+        assert!(!spawn.access().is_compatible(count.access()));
+        assert!(!spawn.access().is_compatible(&Access::default()));
+
+        assert_eq!(spawn.call(3, &mut world)?, 3);
+        assert_eq!(spawn.call(2, &mut world)?, 5);
+        assert_eq!(count.call((), &mut world)?, 5);
+        assert_eq!(world.res::<Spawned>().map(|spawned| spawned.0), Some(5));
+
+        clear.call((), &mut world)?;
+        assert!(world.res::<Spawned>().is_none());
+        Ok(())
+    }
+}
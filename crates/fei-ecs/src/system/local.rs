@@ -0,0 +1,119 @@
+use fei_common::prelude::*;
+use crate::{
+    system::{
+        SystemParam, ReadOnlySystemParam,
+        Access,
+    },
+    world::{
+        World, WorldCell, FromWorld,
+    },
+    ChangeMark,
+};
+use std::{
+    fmt::{
+        Debug, Formatter,
+    },
+    ops::{
+        Deref, DerefMut,
+    },
+};
+
+/// A value owned by the system itself, persisting across calls. Initialized with [`FromWorld`]
+/// when the system state is constructed; each system gets its own, separate value.
+pub struct Local<'state, T: 'static + FromWorld + Send + Sync>(&'state mut T);
+
+// `Local` only ever touches the system state, which is exclusively borrowed anyway.
+unsafe impl<'state, T: 'static + FromWorld + Send + Sync> ReadOnlySystemParam for Local<'state, T> {}
+impl<'state, T: 'static + FromWorld + Send + Sync> SystemParam for Local<'state, T> {
+    type State = T;
+    type Item<'w, 's> = Local<'s, T>;
+    type ReadOnly = Self;
+
+    #[inline]
+    unsafe fn construct<'w, 's>(_: WorldCell<'w>, state: &'s mut Self::State, _: ChangeMark, _: ChangeMark) -> anyhow::Result<Self::Item<'w, 's>> {
+        Ok(Local(state))
+    }
+
+    #[inline]
+    fn construct_state(world: &mut World, _: &mut Access) -> anyhow::Result<Self::State> {
+        Ok(T::from_world(world))
+    }
+}
+
+impl<'state, T: 'static + FromWorld + Send + Sync> Deref for Local<'state, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'state, T: 'static + FromWorld + Send + Sync> DerefMut for Local<'state, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+impl<'state, T: 'static + FromWorld + Send + Sync + Debug> Debug for Local<'state, T> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fei_ecs_macros::Resource;
+    use crate::{
+        resource::Res,
+        system::{
+            System, IntoSystem, SystemState,
+        },
+    };
+
+    #[test]
+    fn local() -> anyhow::Result<()> {
+        #[derive(Resource)]
+        struct Start(u32);
+
+        struct Counter {
+            count: u32,
+        }
+        impl FromWorld for Counter {
+            fn from_world(world: &mut World) -> Self {
+                Self {
+                    count: world.res::<Start>().map_or(0, |start| start.0),
+                }
+            }
+        }
+
+        fn count(mut counter: Local<Counter>, mut calls: Local<usize>) -> anyhow::Result<(u32, usize)> {
+            counter.count += 1;
+            *calls += 1;
+            Ok((counter.count, *calls))
+        }
+
+        let mut world = World::default();
+        world.insert_res(Start(10));
+
+        let mut first = count.into_system(&mut world)?;
+        let mut second = count.into_system(&mut world)?;
+        assert!(first.access().is_compatible(second.access()));
+
+        assert_eq!(first.call((), &mut world)?, (11, 1));
+        assert_eq!(first.call((), &mut world)?, (12, 2));
+        assert_eq!(second.call((), &mut world)?, (11, 1));
+
+        let mut state = SystemState::<(Local<Vec<u32>>, Res<Start>)>::new(&mut world)?;
+        let (mut values, start) = state.get(&world)?;
+        values.push(start.0);
+
+        let (values, ..) = state.get(&world)?;
+        assert_eq!(*values, [10]);
+
+        Ok(())
+    }
+}
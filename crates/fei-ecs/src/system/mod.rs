@@ -1,5 +1,15 @@
+mod access;
+mod adapter;
+mod condition;
 mod def;
+mod exclusive;
+mod local;
 mod state;
 
+pub use access::*;
+pub use adapter::*;
+pub use condition::*;
 pub use def::*;
+pub use exclusive::*;
+pub use local::*;
 pub use state::*;
@@ -1,6 +1,8 @@
 use fei_common::prelude::*;
 use crate::{
-    system::SystemParam,
+    system::{
+        SystemParam, Access,
+    },
     world::{
         World, WorldCell,
     },
@@ -9,18 +11,27 @@ use crate::{
 
 pub struct SystemState<Param: SystemParam> {
     state: Param::State,
+    access: Access,
     last: ChangeMark,
 }
 
 impl<Param: SystemParam> SystemState<Param> {
     #[inline]
     pub fn new(world: &mut World) -> anyhow::Result<Self> {
+        let mut access = Access::default();
         Ok(Self {
-            state: Param::construct_state(world)?,
+            state: Param::construct_state(world, &mut access)?,
+            access,
             last: world.last_change_mark(),
         })
     }
 
+    /// Returns the components and resources the parameters access.
+    #[inline]
+    pub fn access(&self) -> &Access {
+        &self.access
+    }
+
     #[inline]
     pub fn get<'w, 's>(&'s mut self, world: &'w World) -> anyhow::Result<<Param::ReadOnly as SystemParam>::Item<'w, 's>> {
         let current = world.change_mark();
@@ -39,6 +50,12 @@ impl<Param: SystemParam> SystemState<Param> {
         let last = std::mem::replace(&mut self.last, current);
         Param::construct(world, &mut self.state, last, current)
     }
+
+    /// Applies deferred operations recorded by the parameters, e.g. queued [commands](crate::command::Commands).
+    #[inline]
+    pub fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        Param::apply(&mut self.state, world)
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
 use crate::{
+    component::Components,
+    entity::Entities,
     resource::{
         ResourceId,
         ResourceLocalId,
@@ -19,6 +21,11 @@ pub struct WorldCell<'a> {
     _marker: PhantomData<(&'a World, &'a UnsafeCell<World>)>,
 }
 
+// Safety: Accessing the world through the cell is unsafe, and callers are responsible for not
+// creating conflicting accesses across threads.
+unsafe impl Send for WorldCell<'_> {}
+unsafe impl Sync for WorldCell<'_> {}
+
 impl<'a> WorldCell<'a> {
     #[inline]
     pub unsafe fn read(world: &'a World) -> Self {
@@ -41,6 +48,26 @@ impl<'a> WorldCell<'a> {
         &*self.inner
     }
 
+    /// Returns the world mutably, e.g. for [exclusive systems](crate::system::ExclusiveSystemFn).
+    ///
+    /// # Safety
+    /// The cell must have been created with [`write`](WorldCell::write), and there may not be any
+    /// other access to the world while the returned reference is alive.
+    #[inline]
+    pub unsafe fn get_mut(self) -> &'a mut World {
+        &mut *self.inner
+    }
+
+    #[inline]
+    pub unsafe fn components(self) -> &'a Components {
+        &self.get().components
+    }
+
+    #[inline]
+    pub unsafe fn entities(self) -> &'a Entities {
+        &self.get().entities
+    }
+
     #[inline]
     pub unsafe fn res_by_id(self, id: ResourceId, last: ChangeMark) -> Option<RefErased<'a>> {
         self.get().resources.get(id).map(|data| data.as_ref(last))
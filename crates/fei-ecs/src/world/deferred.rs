@@ -0,0 +1,97 @@
+use crate::{
+    command::Commands,
+    component::{
+        Component, ComponentId,
+        ComponentHook, ComponentHooks,
+    },
+    entity::Entity,
+    observer::ObserverKey,
+    resource::Resource,
+    world::World,
+    Mut,
+};
+use std::ops::Deref;
+
+/// A [`World`] that may be read and mutated, but not structurally changed, i.e., entities and
+/// components may not be added or removed. Those changes are queued as [commands](DeferredWorld::commands)
+/// instead, applied once the structural change that ran the hooks ends.
+pub struct DeferredWorld<'w> {
+    world: &'w mut World,
+}
+
+impl<'w> DeferredWorld<'w> {
+    #[inline]
+    pub(crate) fn new(world: &'w mut World) -> Self {
+        Self { world, }
+    }
+
+    #[inline]
+    pub fn reborrow(&mut self) -> DeferredWorld<'_> {
+        DeferredWorld { world: self.world, }
+    }
+
+    /// Returns commands that are applied at the end of the current structural change.
+    #[inline]
+    pub fn commands(&mut self) -> Commands<'_, '_> {
+        Commands::new(&self.world.entities, &mut self.world.commands)
+    }
+
+    #[inline]
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<'_, T>> {
+        self.world.view_mut(entity).ok()?.into_mut()
+    }
+
+    #[inline]
+    pub fn res_mut<T: Resource>(&mut self) -> Option<Mut<'_, T>> {
+        self.world.res_mut()
+    }
+
+    /// Runs the hook `select` picks for each of the given components.
+    #[inline]
+    pub(crate) fn trigger(
+        &mut self, entity: Entity,
+        targets: impl IntoIterator<Item = ComponentId>,
+        select: fn(&ComponentHooks) -> Option<ComponentHook>,
+    ) {
+        for id in targets {
+            if let Some(hook) = self.world.components.get_info(id).and_then(|info| select(info.hooks())) {
+                hook(self.reborrow(), entity, id);
+            }
+        }
+    }
+
+    /// Runs the observers of the lifecycle event `key` maps each of the given components to. Their
+    /// deferred state is applied at the end of the structural change, and errors they return are
+    /// kept for [`World::flush_commands`].
+    #[inline]
+    pub(crate) fn trigger_observers(
+        &mut self, entity: Entity,
+        targets: impl IntoIterator<Item = ComponentId>,
+        key: fn(ComponentId) -> ObserverKey,
+    ) {
+        for id in targets {
+            let key = key(id);
+            if !self.world.observers.contains(key) { continue }
+
+            for observer in self.world.observers.matching(key, Some(entity)) {
+                let Some(mut slot) = self.world.observers.take(observer) else { continue };
+                let result = unsafe { slot.run(self.world.cell_mut(), None, Some(entity)) };
+
+                self.world.observers.restore(observer, slot);
+                self.world.commands.push(move |world: &mut World| {
+                    result?;
+                    world.apply_observer(observer)
+                });
+            }
+        }
+    }
+}
+
+impl<'w> Deref for DeferredWorld<'w> {
+    type Target = World;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.world
+    }
+}
@@ -1,31 +1,49 @@
 use fei_common::prelude::*;
 use crate::{
+    command::CommandQueue,
     component::{
+        Component, ComponentId, ComponentHooks, ComponentDescriptor,
         ComponentSet,
-        Components,
+        Components, RemovedComponentEvents,
     },
     entity::{
         Entity,
         Entities, SpawnError,
+        ReserveEntities, ReserveError,
+    },
+    event::Event,
+    observer::{
+        Observers, ObserverEvent, ObserverId, ObserverKey, Trigger,
+    },
+    query::{
+        QueryData, QueryFilter, QueryState, QueryBuilder,
     },
     resource::{
         Resources,
         Resource, ResourceId,
         ResourceLocal, ResourceLocalId, LocalResult,
     },
-    world::{
-        EntityView, EntityViewMut,
+    system::{
+        IntoSystem, System,
     },
     ChangeMark, Ref, Mut,
 };
-use std::sync::atomic::{
-    AtomicU32, Ordering,
+use std::{
+    any::Any,
+    sync::{
+        Arc,
+        atomic::{
+            AtomicU32, Ordering,
+        },
+    },
 };
 
 mod cell;
+mod deferred;
 mod view;
 
 pub use cell::*;
+pub use deferred::*;
 pub use view::*;
 
 #[derive(Error, Debug)]
@@ -36,6 +54,10 @@ pub struct World {
     components: Components,
     resources: Resources,
     entities: Entities,
+    observers: Observers,
+    commands: CommandQueue,
+    command_error: Option<anyhow::Error>,
+    removed: RemovedComponentEvents,
 
     last: ChangeMark,
     current: AtomicU32,
@@ -48,6 +70,10 @@ impl Default for World {
             components: default(),
             resources: default(),
             entities: default(),
+            observers: default(),
+            commands: default(),
+            command_error: None,
+            removed: default(),
 
             last: ChangeMark::new(0),
             current: AtomicU32::new(1),
@@ -92,25 +118,243 @@ impl World {
     #[inline]
     pub fn spawn_empty(&mut self) -> Result<EntityViewMut, SpawnError> {
         let entity = self.entities.spawn()?;
-        Ok(unsafe { EntityViewMut::new(entity, &mut self.entities, &mut self.components) })
+        let current = self.change_mark_mut();
+        Ok(unsafe { EntityViewMut::new(entity, self, current) })
     }
 
     #[inline]
     pub fn view(&self, entity: Entity) -> Result<EntityView, NonexistentError> {
         self.entities
             .contains(entity)
-            .then(|| unsafe { EntityView::new(entity, &self.entities, &self.components) })
+            .then(|| unsafe { EntityView::new(entity, &self.entities, &self.components, self.last) })
             .ok_or(NonexistentError)
     }
 
     #[inline]
     pub fn view_mut(&mut self, entity: Entity) -> Result<EntityViewMut, NonexistentError> {
+        let current = self.change_mark_mut();
         self.entities
             .contains(entity)
-            .then(|| unsafe { EntityViewMut::new(entity, &mut self.entities, &mut self.components) })
+            .then(|| unsafe { EntityViewMut::new(entity, self, current) })
             .ok_or(NonexistentError)
     }
 
+    #[inline]
+    pub fn despawn(&mut self, entity: Entity) -> Result<(), NonexistentError> {
+        self.view_mut(entity)?.despawn();
+        Ok(())
+    }
+
+    /// Despawns every given entity, skipping ones that don't exist.
+    #[inline]
+    pub fn despawn_many(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        for entity in entities {
+            if let Ok(view) = self.view_mut(entity) {
+                view.despawn();
+            }
+        }
+    }
+
+    #[inline]
+    pub fn components(&self) -> &Components {
+        &self.components
+    }
+
+    #[inline]
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    #[inline]
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    /// Returns the entities that recently lost components, per component.
+    #[inline]
+    pub fn removed_components(&self) -> &RemovedComponentEvents {
+        &self.removed
+    }
+
+    /// [Updates](RemovedComponentEvents::update) the removal buffers read by
+    /// [`RemovedComponents`](crate::component::RemovedComponents). Should be called exactly once per frame.
+    #[inline]
+    pub fn update_removed_components(&mut self) {
+        self.removed.update();
+    }
+
+    #[inline]
+    pub(crate) fn init_removed_components(&mut self, id: ComponentId) {
+        self.removed.init(id);
+    }
+
+    #[inline]
+    pub fn observers(&self) -> &Observers {
+        &self.observers
+    }
+
+    /// Registers a global observer system of `E`, ran whenever `E` is triggered.
+    #[inline]
+    pub fn observe<E: ObserverEvent, M>(&mut self, system: impl IntoSystem<M, In = Trigger<E>, Out = ()>) -> anyhow::Result<ObserverId> {
+        self.observe_impl(None, system)
+    }
+
+    /// Registers an observer system of `E`, ran whenever `E` is triggered for any of the given entities.
+    /// It's removed once all of them are despawned.
+    #[inline]
+    pub fn observe_targets<E: ObserverEvent, M>(
+        &mut self, targets: impl IntoIterator<Item = Entity>,
+        system: impl IntoSystem<M, In = Trigger<E>, Out = ()>,
+    ) -> anyhow::Result<ObserverId> {
+        self.observe_impl(Some(targets.into_iter().collect()), system)
+    }
+
+    #[inline]
+    fn observe_impl<E: ObserverEvent, M>(
+        &mut self, targets: Option<Vec<Entity>>,
+        system: impl IntoSystem<M, In = Trigger<E>, Out = ()>,
+    ) -> anyhow::Result<ObserverId> {
+        let key = E::key(self);
+        let system = Box::new(system.into_system(self)?);
+
+        // Observers may run amid structural changes, e.g. from component hooks.
+        anyhow::ensure!(!system.access().is_exclusive(), "observer `{}` may not take the world exclusively", system.name());
+        Ok(self.observers.insert(key, targets, system))
+    }
+
+    /// Removes an observer, returning whether it existed.
+    #[inline]
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        self.observers.remove(id)
+    }
+
+    /// Runs every global observer of `E`, applying their deferred state right away.
+    #[inline]
+    pub fn trigger<E: Event>(&mut self, event: E) -> anyhow::Result<()> {
+        let key = E::key(self);
+        self.run_observers(key, Arc::new(event), None)
+    }
+
+    /// Runs the observers of `E` for each of the given entities: global ones, and ones targeting
+    /// the entity.
+    #[inline]
+    pub fn trigger_targets<E: Event>(&mut self, event: E, targets: impl IntoIterator<Item = Entity>) -> anyhow::Result<()> {
+        let key = E::key(self);
+        let event = Arc::new(event);
+        for target in targets {
+            self.run_observers(key, event.clone(), Some(target))?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn run_observers(&mut self, key: ObserverKey, event: Arc<dyn Any + Send + Sync>, entity: Option<Entity>) -> anyhow::Result<()> {
+        for id in self.observers.matching(key, entity) {
+            let Some(mut slot) = self.observers.take(id) else { continue };
+            let result = unsafe { slot.run(self.cell_mut(), Some(event.clone()), entity) }.and_then(|_| slot.apply(self));
+
+            self.observers.restore(id, slot);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies the deferred state of an observer ran by a [`DeferredWorld`].
+    #[inline]
+    pub(crate) fn apply_observer(&mut self, id: ObserverId) -> anyhow::Result<()> {
+        let Some(mut slot) = self.observers.take(id) else { return Ok(()) };
+        let result = slot.apply(self);
+
+        self.observers.restore(id, slot);
+        result
+    }
+
+    /// Reserves an entity that only exists after the next [`flush_entities`](World::flush_entities),
+    /// which also happens implicitly on [spawning](World::spawn).
+    #[inline]
+    pub fn reserve_entity(&self) -> Result<Entity, ReserveError> {
+        self.entities.reserve()
+    }
+
+    /// Reserves `count` entities, similar to [`reserve_entity`](World::reserve_entity).
+    #[inline]
+    pub fn reserve_entities(&self, count: usize) -> Result<ReserveEntities<'_>, ReserveError> {
+        self.entities.reserve_many(count)
+    }
+
+    /// Validates every reserved entity, without any components.
+    #[inline]
+    pub fn flush_entities(&mut self) {
+        self.entities.flush();
+    }
+
+    /// Applies the commands queued by [component hooks](ComponentHooks) through their [`DeferredWorld`].
+    /// [Command queues](CommandQueue::apply) do this after every command they apply, and structural
+    /// changes through [`EntityViewMut`] before they return, keeping the first error for this to return.
+    #[inline]
+    pub fn flush_commands(&mut self) -> anyhow::Result<()> {
+        let result = self.apply_commands();
+        match self.command_error.take() {
+            Some(error) => Err(error),
+            None => result,
+        }
+    }
+
+    /// Applies the commands queued during a structural change, keeping the first error for the next
+    /// [`flush_commands`](World::flush_commands).
+    #[inline]
+    pub(crate) fn flush_structural(&mut self) {
+        if let Err(error) = self.apply_commands() {
+            self.command_error.get_or_insert(error);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn apply_commands(&mut self) -> anyhow::Result<()> {
+        while !self.commands.is_empty() {
+            std::mem::take(&mut self.commands).apply(self)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn register_component<T: Component>(&mut self) -> ComponentId {
+        self.components.register::<T>()
+    }
+
+    /// Registers a component described at runtime, returning a new ID each call.
+    #[inline]
+    pub fn register_component_with_descriptor(&mut self, descriptor: ComponentDescriptor) -> ComponentId {
+        self.components.register_descriptor(descriptor)
+    }
+
+    /// Returns the lifecycle hooks of a component type for modification, registering it if needed.
+    #[inline]
+    pub fn component_hooks_mut<T: Component>(&mut self) -> &mut ComponentHooks {
+        let id = self.components.register::<T>();
+        unsafe { self.components.hooks_mut(id).unwrap_unchecked() }
+    }
+
+    #[inline]
+    pub fn query<Q: QueryData>(&mut self) -> QueryState<Q> {
+        QueryState::new(self)
+    }
+
+    #[inline]
+    pub fn query_filtered<Q: QueryData, F: QueryFilter>(&mut self) -> QueryState<Q, F> {
+        QueryState::new(self)
+    }
+
+    /// Assembles a query from [`ComponentId`]s, e.g. of components registered with
+    /// [`register_component_with_descriptor`](World::register_component_with_descriptor).
+    #[inline]
+    pub fn query_builder(&self) -> QueryBuilder<'_> {
+        QueryBuilder::new(self)
+    }
+
     #[inline]
     pub fn register_res<T: Resource>(&mut self) -> ResourceId {
         self.resources.register::<T>()
@@ -194,7 +438,7 @@ impl World {
 
     #[inline]
     pub fn cell_mut(&mut self) -> WorldCell {
-        unsafe { WorldCell::read(self) }
+        unsafe { WorldCell::write(self) }
     }
 }
 
@@ -213,6 +457,7 @@ impl<T: Default> FromWorld for T {
 mod tests {
     use super::*;
     use fei_ecs_macros::Component;
+    use crate::ChangeAware;
 
     #[test]
     fn viewing() -> anyhow::Result<()> {
@@ -226,9 +471,9 @@ mod tests {
         let mut world = World::default();
         let fei = {
             let mut fei = world.spawn((Name("fei".to_string()), Height(-100.0)))?;
-            assert_eq!(fei.get::<Name>(), Some(&Name("fei".to_string())));
-            assert_eq!(fei.get_mut::<Height>(), Some(&mut Height(-100.0)));
-            assert_eq!(fei.get::<LoveInterest>(), None);
+            assert_eq!(fei.get::<Name>().as_deref(), Some(&Name("fei".to_string())));
+            assert_eq!(fei.get_mut::<Height>().as_deref_mut(), Some(&mut Height(-100.0)));
+            assert!(fei.get::<LoveInterest>().is_none());
 
             let Some((name, height)) = fei.extract::<(Name, Height)>() else { anyhow::bail!("Invalid components") };
             assert_eq!(name.0, "fei");
@@ -247,8 +492,228 @@ mod tests {
             who_knows
         };
 
-        assert_eq!(world.view(fei)?.get::<LoveInterest>(), Some(&LoveInterest(who_knows)));
-        assert_eq!(world.view(who_knows)?.get::<LoveInterest>(), Some(&LoveInterest(fei)));
+        assert_eq!(world.view(fei)?.get::<LoveInterest>().as_deref(), Some(&LoveInterest(who_knows)));
+        assert_eq!(world.view(who_knows)?.get::<LoveInterest>().as_deref(), Some(&LoveInterest(fei)));
+        Ok(())
+    }
+
+    #[test]
+    fn change_ticks() -> anyhow::Result<()> {
+        #[derive(Component)]
+        struct Health(u32);
+        #[derive(Component)]
+        #[component(storage = "SparseSet")]
+        struct Poisoned(u32);
+        #[derive(Component)]
+        struct Player;
+
+        let mut world = World::default();
+        let player = world.spawn((Health(100), Poisoned(5), Player))?.id();
+
+        {
+            let view = world.view(player)?;
+            let health = view.get::<Health>().unwrap();
+            let poisoned = view.get::<Poisoned>().unwrap();
+            let marker = view.get::<Player>().unwrap();
+            assert!(health.is_added() && poisoned.is_added() && marker.is_added());
+        }
+
+        world.sync_change_mark();
+        {
+            let mut view = world.view_mut(player)?;
+            let damage = view.get::<Poisoned>().unwrap().0;
+            view.get_mut::<Health>().unwrap().0 -= damage;
+
+            // Reading through `Mut` doesn't count as an update.
+            assert_eq!(view.get_mut::<Poisoned>().unwrap().0, 5);
+        }
+
+        let view = world.view(player)?;
+        let health = view.get::<Health>().unwrap();
+        let poisoned = view.get::<Poisoned>().unwrap();
+        assert_eq!(health.0, 95);
+        assert!(!health.is_added() && health.is_updated());
+        assert!(!poisoned.is_added() && !poisoned.is_updated());
+        Ok(())
+    }
+
+    #[test]
+    fn despawning() -> anyhow::Result<()> {
+        use std::sync::{
+            Arc,
+            atomic::AtomicUsize,
+        };
+
+        #[derive(Component)]
+        struct Tracked(u32, Arc<AtomicUsize>);
+        impl Drop for Tracked {
+            #[inline]
+            fn drop(&mut self) {
+                self.1.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        #[derive(Component)]
+        #[component(storage = "SparseSet")]
+        struct Sparse(u32);
+        #[derive(Component)]
+        struct Marker;
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let mut world = World::default();
+        let all = (0..4)
+            .map(|i| Ok(world.spawn((Tracked(i, dropped.clone()), Sparse(i), Marker))?.id()))
+            .collect::<Result<Vec<_>, SpawnError>>()?;
+
+        world.despawn(all[0])?;
+        assert!(world.view(all[0]).is_err());
+        assert!(world.despawn(all[0]).is_err());
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+
+        // The last entity has been swapped into the despawned one's table row.
+        let view = world.view(all[3])?;
+        assert_eq!(view.get::<Tracked>().unwrap().0, 3);
+        assert_eq!(view.get::<Sparse>().unwrap().0, 3);
+        assert!(view.contains::<Marker>());
+
+        world.view_mut(all[1])?.despawn();
+        world.despawn_many([all[0], all[2], all[3]]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 4);
+        assert!(all.iter().all(|&entity| world.view(entity).is_err()));
+        Ok(())
+    }
+
+    #[test]
+    fn reservation() -> anyhow::Result<()> {
+        #[derive(Component, Debug, Eq, PartialEq)]
+        struct Id(u32);
+
+        let mut world = World::default();
+        let a = world.reserve_entity()?;
+        let many = world.reserve_entities(3)?.collect::<Vec<_>>();
+        assert!(world.view(a).is_err());
+
+        // Spawning flushes pending reservations instead of failing.
+        let b = world.spawn(Id(1))?.id();
+        assert!(world.view(a)?.get::<Id>().is_none());
+        assert!(many.iter().all(|&entity| world.view(entity).is_ok()));
+        assert!(b.id() > many[2].id());
+
+        world.view_mut(a)?.insert(Id(0));
+        assert_eq!(world.view(a)?.get::<Id>().as_deref(), Some(&Id(0)));
+
+        // Despawning also flushes, so the freed entity isn't handed to an earlier reservation.
+        let c = world.reserve_entity()?;
+        world.despawn(b)?;
+        let d = world.reserve_entity()?;
+        world.flush_entities();
+        assert!(world.view(c).is_ok());
+        assert!(world.view(d).is_ok());
+        assert_eq!(d.id(), b.id());
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_components() -> anyhow::Result<()> {
+        use crate::{
+            component::{
+                ComponentInfo, ComponentStorage,
+            },
+            query::With,
+            ChangeAwareMut,
+        };
+        use fei_common::ptr::PtrOwned;
+        use std::{
+            alloc::Layout,
+            mem::MaybeUninit,
+        };
+
+        static DROPPED: AtomicU32 = AtomicU32::new(0);
+        unsafe fn count_drop(_: *mut u8) {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+
+        unsafe fn clone_u64(from: *const u8, to: *mut u8) {
+            to.cast::<u64>().write(from.cast::<u64>().read());
+        }
+
+        #[derive(Component)]
+        struct Marker;
+
+        let mut world = World::default();
+        let (health, tag) = unsafe {(
+            world.register_component_with_descriptor(ComponentDescriptor::new("health", Layout::new::<u64>(), ComponentStorage::Table)
+                .with_dropper(count_drop)
+                .with_cloner(clone_u64)
+            ),
+            world.register_component_with_descriptor(ComponentDescriptor::new("tag", Layout::new::<u32>(), ComponentStorage::SparseSet)),
+        )};
+        assert_eq!(world.components().get_info(health).map(ComponentInfo::name), Some("health"));
+        let marker = world.register_component::<Marker>();
+        assert!(world.components().get_info(marker).is_some_and(|info| info.name().ends_with("Marker")));
+
+        let a = world.spawn(Marker)?.id();
+        let b = world.spawn_empty()?.id();
+        unsafe {
+            let mut view = world.view_mut(a)?;
+            PtrOwned::take(42u64, |health_ptr| PtrOwned::take(7u32, |tag_ptr| view.insert_by_ids(&[health, tag], [health_ptr, tag_ptr])));
+
+            let mut view = world.view_mut(b)?;
+            PtrOwned::take(5u64, |health_ptr| view.insert_by_ids(&[health], [health_ptr]));
+            *view.get_erased_mut(health).unwrap().get_mut().deref_mut::<u64>() += 1;
+        }
+
+        let read = |entity, id| world.view(entity).ok()?.get_erased(id).map(|component| unsafe { *component.get().deref::<u32>() });
+        assert_eq!(read(a, tag), Some(7));
+        assert_eq!(read(b, tag), None);
+        assert_eq!(world.view(b)?.get_erased(health).map(|component| unsafe { *component.get().deref::<u64>() }), Some(6));
+
+        // Values may be cloned out of the storage with the descriptor's cloner.
+        let cloner = world.components().get_info(health).and_then(ComponentInfo::cloner).unwrap();
+        let mut cloned = MaybeUninit::<u64>::uninit();
+        unsafe {
+            let component = world.view(b)?.get_erased(health).unwrap();
+            cloner((component.get().deref::<u64>() as *const u64).cast(), cloned.as_mut_ptr().cast());
+            assert_eq!(cloned.assume_init(), 6);
+        }
+
+        // Queries may match dynamic components by their IDs.
+        let mut query = world.query::<Entity>().with_id(health).without_id(tag);
+        assert_eq!(query.iter(&world).collect::<Vec<_>>(), [b]);
+        let mut query = world.query_filtered::<Entity, With<Marker>>().with_id(tag);
+        assert_eq!(query.iter(&world).collect::<Vec<_>>(), [a]);
+
+        world.view_mut(a)?.remove_by_ids(&[health, tag]);
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+        assert!(world.view(a)?.contains::<Marker>());
+        assert!(world.view(a)?.get_erased(health).is_none());
+
+        world.despawn(b)?;
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn cell_mut() -> anyhow::Result<()> {
+        #[derive(fei_ecs_macros::Resource)]
+        struct Counter(u32);
+
+        let mut world = World::default();
+        let id = world.register_res::<Counter>();
+        world.insert_res(Counter(0));
+
+        let current = world.change_mark_mut();
+        let last = world.last_change_mark();
+        let cell = world.cell_mut();
+
+        // Safety: The cell is derived from a mutable borrow, and nothing else accesses the world.
+        unsafe {
+            cell.res_by_id_mut(id, last, current).unwrap().casted::<Counter>().0 += 1;
+            let count = cell.get().res::<Counter>().unwrap().0;
+            cell.get_mut().insert_res(Counter(count + 1));
+        }
+
+        assert_eq!(world.res::<Counter>().map(|counter| counter.0), Some(2));
         Ok(())
     }
 }
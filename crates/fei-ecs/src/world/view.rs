@@ -1,5 +1,8 @@
-use fei_common::ptr::{
-    Ptr, PtrMut, PtrOwned,
+use fei_common::{
+    prelude::*,
+    ptr::{
+        PtrMut, PtrOwned,
+    },
 };
 use crate::{
     component::{
@@ -10,18 +13,37 @@ use crate::{
     entity::{
         Entity, Entities,
     },
+    observer::{
+        ObserverEvent, ObserverId, ObserverKey, Trigger,
+    },
+    system::IntoSystem,
+    world::{
+        World, DeferredWorld,
+    },
+    ChangeMark,
+    Ref, RefErased,
+    Mut, MutErased,
+};
+use std::{
+    alloc::{
+        alloc, dealloc, handle_alloc_error,
+    },
+    ptr::NonNull,
 };
 
 pub struct EntityView<'a> {
     entity: Entity,
     entities: &'a Entities,
     components: &'a Components,
+    last: ChangeMark,
 }
 
 impl<'a> EntityView<'a> {
+    /// # Safety
+    /// `entity` must exist in `entities`, and `components` must be the components of the same world.
     #[inline]
-    pub unsafe fn new(entity: Entity, entities: &'a Entities, components: &'a Components) -> Self {
-        Self { entity, entities, components, }
+    pub unsafe fn new(entity: Entity, entities: &'a Entities, components: &'a Components, last: ChangeMark) -> Self {
+        Self { entity, entities, components, last, }
     }
 
     #[inline]
@@ -46,36 +68,40 @@ impl<'a> EntityView<'a> {
     }
 
     #[inline]
-    pub fn get<T: Component>(&self) -> Option<&T> {
-        self.components
-            .get_id::<T>()
-            .and_then(|id| unsafe {
-                self.entities
-                    .location(self.entity)
-                    .and_then(|loc| self.components
-                        .contains(self.entity, loc, id)
-                        .then(|| self.components.get(self.entity, loc, id).deref())
-                    )
-            })
+    pub fn get<T: Component>(&self) -> Option<Ref<'a, T>> {
+        let id = self.components.get_id::<T>()?;
+        self.contains_id(id).then(|| unsafe { self.get_by_id(id).casted() })
+    }
+
+    /// Returns a type-erased component, e.g. one registered from a [`ComponentDescriptor`](crate::component::ComponentDescriptor),
+    /// or `None` if the entity doesn't hold it. See [`get_by_id`](Self::get_by_id) for an unchecked version.
+    #[inline]
+    pub fn get_erased(&self, id: ComponentId) -> Option<RefErased<'a>> {
+        self.contains_id(id).then(|| unsafe { self.get_by_id(id) })
     }
 
+    /// # Safety
+    /// The entity must hold the component `id`.
     #[inline]
-    pub unsafe fn get_by_id(&self, id: ComponentId) -> Ptr {
+    pub unsafe fn get_by_id(&self, id: ComponentId) -> RefErased<'a> {
         let loc = self.entities.location(self.entity).unwrap_unchecked();
-        self.components.get(self.entity, loc, id)
+        let ticks = self.components.get_ticks(self.entity, loc, id);
+        RefErased::new(self.components.get(self.entity, loc, id), ticks.added(), ticks.updated(), self.last)
     }
 }
 
 pub struct EntityViewMut<'a> {
     entity: Entity,
-    entities: &'a mut Entities,
-    components: &'a mut Components,
+    world: &'a mut World,
+    current: ChangeMark,
 }
 
 impl<'a> EntityViewMut<'a> {
+    /// # Safety
+    /// `entity` must exist in `world`.
     #[inline]
-    pub unsafe fn new(entity: Entity, entities: &'a mut Entities, components: &'a mut Components) -> Self {
-        Self { entity, entities, components, }
+    pub unsafe fn new(entity: Entity, world: &'a mut World, current: ChangeMark) -> Self {
+        Self { entity, world, current, }
     }
 
     #[inline]
@@ -85,7 +111,7 @@ impl<'a> EntityViewMut<'a> {
 
     #[inline]
     pub fn contains<T: Component>(&self) -> bool {
-        self.components
+        self.world.components
             .get_id::<T>()
             .is_some_and(|id| self.contains_id(id))
     }
@@ -93,70 +119,323 @@ impl<'a> EntityViewMut<'a> {
     #[inline]
     pub fn contains_id(&self, id: ComponentId) -> bool {
         unsafe {
-            self.entities
+            self.world.entities
                 .location(self.entity)
-                .is_some_and(|loc| self.components.contains(self.entity, loc, id))
+                .is_some_and(|loc| self.world.components.contains(self.entity, loc, id))
         }
     }
 
     #[inline]
-    pub fn get<T: Component>(&self) -> Option<&T> {
-        self.components
-            .get_id::<T>()
-            .and_then(|id| unsafe {
-                self.entities
-                    .location(self.entity)
-                    .and_then(|loc| self.components
-                        .contains(self.entity, loc, id)
-                        .then(|| self.components.get(self.entity, loc, id).deref())
-                    )
-            })
+    pub fn get<T: Component>(&self) -> Option<Ref<'_, T>> {
+        let id = self.world.components.get_id::<T>()?;
+        self.contains_id(id).then(|| unsafe { self.get_by_id(id).casted() })
     }
 
+    /// Returns a type-erased component, e.g. one registered from a [`ComponentDescriptor`](crate::component::ComponentDescriptor),
+    /// or `None` if the entity doesn't hold it. See [`get_by_id`](Self::get_by_id) for an unchecked version.
     #[inline]
-    pub unsafe fn get_by_id(&self, id: ComponentId) -> Ptr {
-        let loc = self.entities.location(self.entity).unwrap_unchecked();
-        self.components.get(self.entity, loc, id)
+    pub fn get_erased(&self, id: ComponentId) -> Option<RefErased<'_>> {
+        self.contains_id(id).then(|| unsafe { self.get_by_id(id) })
     }
 
+    /// # Safety
+    /// The entity must hold the component `id`.
     #[inline]
-    pub fn get_mut<T: Component>(&mut self) -> Option<&mut T> {
-        let id = self.components.register::<T>();
-        unsafe {
-            self.entities
-                .location(self.entity)
-                .and_then(|loc| self.components
-                    .contains(self.entity, loc, id)
-                    .then(|| self.components.get_mut(self.entity, loc, id).deref_mut())
-                )
-        }
+    pub unsafe fn get_by_id(&self, id: ComponentId) -> RefErased<'_> {
+        let loc = self.world.entities.location(self.entity).unwrap_unchecked();
+        let ticks = self.world.components.get_ticks(self.entity, loc, id);
+        RefErased::new(self.world.components.get(self.entity, loc, id), ticks.added(), ticks.updated(), self.world.last)
     }
 
     #[inline]
-    pub unsafe fn get_by_id_mut(&mut self, id: ComponentId) -> PtrMut {
-        let loc = self.entities.location(self.entity).unwrap_unchecked();
-        self.components.get_mut(self.entity, loc, id)
+    pub fn get_mut<T: Component>(&mut self) -> Option<Mut<'_, T>> {
+        let id = self.world.components.get_id::<T>()?;
+        self.contains_id(id).then(|| unsafe { self.get_by_id_mut(id).casted() })
+    }
+
+    /// Returns a type-erased component mutably, e.g. one registered from a [`ComponentDescriptor`](crate::component::ComponentDescriptor),
+    /// or `None` if the entity doesn't hold it. See [`get_by_id_mut`](Self::get_by_id_mut) for an unchecked version.
+    #[inline]
+    pub fn get_erased_mut(&mut self, id: ComponentId) -> Option<MutErased<'_>> {
+        self.contains_id(id).then(|| unsafe { self.get_by_id_mut(id) })
+    }
+
+    /// # Safety
+    /// The entity must hold the component `id`.
+    #[inline]
+    pub unsafe fn get_by_id_mut(&mut self, id: ComponentId) -> MutErased<'_> {
+        let loc = self.world.entities.location(self.entity).unwrap_unchecked();
+        let (ptr, ticks) = self.world.components.get_mut_with_ticks(self.entity, loc, id);
+        MutErased::new(ptr, &ticks.added, &ticks.updated, self.world.last, self.current)
+    }
+
+    /// Like [`get_mut`](EntityViewMut::get_mut), but borrows for as long as the view itself.
+    #[inline]
+    pub fn into_mut<T: Component>(self) -> Option<Mut<'a, T>> {
+        let id = self.world.components.get_id::<T>()?;
+        self.contains_id(id).then(|| unsafe {
+            let loc = self.world.entities.location(self.entity).unwrap_unchecked();
+            let last = self.world.last;
+            let (ptr, ticks) = self.world.components.get_mut_with_ticks(self.entity, loc, id);
+            MutErased::new(ptr, &ticks.added, &ticks.updated, last, self.current).casted()
+        })
     }
 
+    /// Inserts a component set, running the [hooks](crate::component::ComponentHooks) of its components
+    /// and applying the commands they queue.
+    ///
+    /// # Panics
+    /// Panics if the entity was despawned by a hook or observer.
     #[inline]
     pub fn insert<T: ComponentSet>(&mut self, set: T) {
-        let id = self.components.register_set::<T>();
+        self.assert_exists();
+        let id = self.world.components.register_set::<T>();
         PtrOwned::take(set, |ptr| unsafe { self.insert_by_id(ptr, id) });
     }
 
+    /// Inserts a type-erased component set, running the [hooks](crate::component::ComponentHooks) of its
+    /// components and applying the commands they queue.
+    ///
+    /// # Panics
+    /// Panics if the entity was despawned by a hook or observer.
+    ///
+    /// # Safety
+    /// `set` must point to a valid value of the component set `set_id` was registered from.
     #[inline]
     pub unsafe fn insert_by_id(&mut self, set: PtrOwned<'static>, set_id: ComponentSetId) {
-        self.components.insert(self.entity, self.entities, set, set_id)
+        self.assert_exists();
+        let hooked = self.hooked(set_id);
+        if let Some(hooked) = &hooked {
+            DeferredWorld::new(self.world).trigger(self.entity, Self::held(hooked, true), |hooks| hooks.on_replace);
+        }
+
+        let world = &mut *self.world;
+        world.components.insert(self.entity, &mut world.entities, set, set_id, self.current);
+
+        if let Some(hooked) = hooked {
+            let mut world = DeferredWorld::new(self.world);
+            world.trigger(self.entity, Self::held(&hooked, false), |hooks| hooks.on_add);
+            world.trigger(self.entity, hooked.iter().map(|&(id, ..)| id), |hooks| hooks.on_insert);
+            world.trigger_observers(self.entity, Self::held(&hooked, false), ObserverKey::Add);
+        }
+
+        self.world.flush_structural();
     }
 
+    /// Inserts components by their IDs, e.g. ones registered from a [`ComponentDescriptor`](crate::component::ComponentDescriptor),
+    /// each moved out of its respective pointer in `components`. Does nothing if `ids` is empty.
+    ///
+    /// # Panics
+    /// Panics if the entity was despawned by a hook or observer.
+    ///
+    /// # Safety
+    /// - Every component must be registered in this world, and given at most once.
+    /// - `components` must yield a pointer for each ID, pointing to a valid value of the component.
+    pub unsafe fn insert_by_ids<'p>(&mut self, ids: &[ComponentId], components: impl IntoIterator<Item = PtrOwned<'p>>) {
+        self.assert_exists();
+        let Some((set_id, layout)) = self.world.components.register_dynamic_set(ids) else { return };
+        let base = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            NonNull::new(alloc(layout)).unwrap_or_else(|| handle_alloc_error(layout))
+        };
+
+        // Move the components into a buffer laid out as the set.
+        let registry = &self.world.components;
+        let set = registry.get_set_info(set_id).unwrap_unchecked();
+        for (&id, component) in ids.iter().zip(components) {
+            let size = registry.get_info(id).unwrap_unchecked().layout().size();
+            PtrMut::new(base)
+                .byte_add(set.offset(id).unwrap_unchecked())
+                .write(component, size);
+        }
+
+        self.insert_by_id(PtrOwned::new(base), set_id);
+        if layout.size() != 0 {
+            dealloc(base.as_ptr(), layout);
+        }
+    }
+
+    /// Removes and returns the component set if the entity holds all of it, running the [hooks](crate::component::ComponentHooks)
+    /// of its components and applying the commands they queue.
+    ///
+    /// # Panics
+    /// Panics if the entity was despawned by a hook or observer.
     #[inline]
     pub fn extract<T: ComponentSet>(&mut self) -> Option<T> {
-        unsafe { self.components.extract_as(self.entity, self.entities) }
+        self.assert_exists();
+        let set_id = self.world.components.register_set::<T>();
+        if let Some(hooked) = self.hooked(set_id) {
+            // Extraction only happens if the entity holds every component of the set.
+            if hooked.iter().all(|&(.., held)| held) {
+                let mut world = DeferredWorld::new(self.world);
+                world.trigger(self.entity, Self::held(&hooked, true), |hooks| hooks.on_remove);
+                world.trigger_observers(self.entity, Self::held(&hooked, true), ObserverKey::Remove);
+            }
+        }
+
+        // Extraction only succeeds if the entity holds every component, so record beforehand.
+        let held = self.contains_set(set_id);
+        if held {
+            self.record_removals(Some(set_id));
+        }
+
+        let world = &mut *self.world;
+        let set = unsafe { world.components.extract_as(self.entity, &mut world.entities) };
+        debug_assert_eq!(held, set.is_some());
+
+        world.flush_structural();
+        set
     }
 
+    /// Removes the components of the set the entity holds, running their [hooks](crate::component::ComponentHooks)
+    /// and applying the commands they queue.
+    ///
+    /// # Panics
+    /// Panics if the entity was despawned by a hook or observer.
     #[inline]
     pub fn remove<T: ComponentSet>(&mut self) {
-        let id = self.components.register_set::<T>();
-        unsafe { self.components.remove(self.entity, self.entities, id) }
+        let set_id = self.world.components.register_set::<T>();
+        self.remove_set(set_id);
+    }
+
+    /// Removes components by their IDs, ignoring ones the entity doesn't hold. Does nothing if any of
+    /// them isn't registered.
+    ///
+    /// # Panics
+    /// Panics if any component is given more than once, or if the entity was despawned by a hook or
+    /// observer.
+    #[inline]
+    pub fn remove_by_ids(&mut self, ids: &[ComponentId]) {
+        if let Some((set_id, ..)) = self.world.components.register_dynamic_set(ids) {
+            self.remove_set(set_id);
+        }
+    }
+
+    #[inline]
+    fn remove_set(&mut self, set_id: ComponentSetId) {
+        self.assert_exists();
+        if let Some(hooked) = self.hooked(set_id) {
+            let mut world = DeferredWorld::new(self.world);
+            world.trigger(self.entity, Self::held(&hooked, true), |hooks| hooks.on_remove);
+            world.trigger_observers(self.entity, Self::held(&hooked, true), ObserverKey::Remove);
+        }
+
+        self.record_removals(Some(set_id));
+        let world = &mut *self.world;
+        unsafe { world.components.remove(self.entity, &mut world.entities, set_id) };
+        world.flush_structural();
+    }
+
+    /// Drops every component of the entity and frees it, invalidating its ID, then applies the commands
+    /// queued by [hooks](crate::component::ComponentHooks). Does nothing if a hook or observer already
+    /// despawned it.
+    #[inline]
+    pub fn despawn(mut self) {
+        if !self.world.entities.contains(self.entity) { return }
+        self.record_removals(None);
+
+        let world = self.world;
+        if let Some(loc) = unsafe { world.entities.location(self.entity) } {
+            let (components, observers) = (&world.components, &world.observers);
+            let hooked = components.archetypes()[loc.archetype_id.0].component_bits()
+                .ones().map(ComponentId)
+                .filter(|&id|
+                    components.get_info(id).is_some_and(|info| info.hooks().on_remove.is_some()) ||
+                    observers.contains(ObserverKey::Remove(id))
+                )
+                .collect::<Vec<_>>();
+
+            let mut deferred = DeferredWorld::new(world);
+            deferred.trigger(self.entity, hooked.iter().copied(), |hooks| hooks.on_remove);
+            deferred.trigger_observers(self.entity, hooked, ObserverKey::Remove);
+        }
+
+        unsafe { world.components.clear(self.entity, &mut world.entities) };
+        world.entities.free(self.entity);
+
+        // Observers targeting the entity may still have deferred state to apply.
+        if world.observers.is_targeting(self.entity) {
+            let entity = self.entity;
+            world.commands.push(move |world: &mut World| {
+                world.observers.remove_target(entity);
+                Ok(())
+            });
+        }
+
+        world.flush_structural();
+    }
+
+    /// Returns the components of the set paired with whether the entity holds them, or `None` if
+    /// none of them have hooks or observers.
+    #[inline]
+    fn hooked(&self, set_id: ComponentSetId) -> Option<Vec<(ComponentId, bool)>> {
+        let (components, observers) = (&self.world.components, &self.world.observers);
+        let set = components.get_set_info(set_id)?.components();
+        set.iter()
+            .any(|&id|
+                components.get_info(id).is_some_and(|info| !info.hooks().is_empty()) ||
+                observers.contains(ObserverKey::Add(id)) ||
+                observers.contains(ObserverKey::Remove(id))
+            )
+            .then(|| set.iter().map(|&id| (id, self.contains_id(id))).collect())
+    }
+
+    /// Returns whether the entity holds every component of the set.
+    #[inline]
+    fn contains_set(&self, set_id: ComponentSetId) -> bool {
+        self.world.components
+            .get_set_info(set_id)
+            .is_some_and(|set| set.components().iter().all(|&id| self.contains_id(id)))
+    }
+
+    /// Records the components of the set the entity holds as [removed](crate::component::RemovedComponents),
+    /// or every component it holds if `set_id` is `None`.
+    #[inline]
+    fn record_removals(&mut self, set_id: Option<ComponentSetId>) {
+        let entity = self.entity;
+        let world = &mut *self.world;
+        let Some(loc) = (unsafe { world.entities.location(entity) }) else { return };
+
+        let (components, removed) = (&world.components, &mut world.removed);
+        let mut record = |id| if unsafe { components.contains(entity, loc, id) } {
+            removed.send(id, entity);
+        };
+
+        match set_id {
+            Some(set_id) => components
+                .get_set_info(set_id).into_iter()
+                .flat_map(|set| set.components().iter().copied())
+                .for_each(&mut record),
+            None => components.archetypes()[loc.archetype_id.0]
+                .component_bits().ones()
+                .map(ComponentId)
+                .for_each(record),
+        }
+    }
+
+    /// Registers an observer system of `E` that only runs when `E` is triggered for this entity. It's
+    /// removed once the entity is despawned.
+    #[inline]
+    pub fn observe<E: ObserverEvent, M>(&mut self, system: impl IntoSystem<M, In = Trigger<E>, Out = ()>) -> anyhow::Result<ObserverId> {
+        self.world.observe_targets([self.entity], system)
+    }
+
+    /// Gives mutable access to the whole world. `f` must not despawn this entity.
+    #[inline]
+    pub(crate) fn world_scope<R>(&mut self, f: impl FnOnce(&mut World) -> R) -> R {
+        f(self.world)
+    }
+
+    /// Hooks and observers may despawn the entity through the commands applied after structural changes.
+    #[inline]
+    #[track_caller]
+    fn assert_exists(&self) {
+        assert!(self.world.entities.contains(self.entity), "entity {:?} was despawned by a hook or observer", self.entity);
+    }
+
+    #[inline]
+    fn held(hooked: &[(ComponentId, bool)], held: bool) -> impl Iterator<Item = ComponentId> + '_ {
+        hooked.iter().filter(move |&&(.., h)| h == held).map(|&(id, ..)| id)
     }
 }